@@ -1,3 +1,29 @@
 pub const ISEND_ID: u64 = 125;
+pub const SET_DAPP_METADATA_ID: u64 = 126;
+pub const I_RECEIVE_ID: u64 = 127;
 pub const MIN_NAME_LENGTH: u64 = 3;
 pub const MAX_NAME_LENGTH: u64 = 64;
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 30;
+// ack types understood by the router gateway: none, on success, on error, always
+pub const MAX_ACK_TYPE: u8 = 3;
+// packed request metadata: 4 x u64, u128 relayer fees, u8 ack type and a bool,
+// followed by the asm address string
+pub const REQUEST_METADATA_FIXED_LENGTH: usize = 50;
+pub const DEFAULT_MAX_RETRY_ATTEMPTS: u32 = 3;
+pub const MAX_BATCH_SIZE: usize = 20;
+pub const DEFAULT_ISEND_VERSION: u64 = 1;
+pub const EVM_ADDRESS_LENGTH: usize = 40;
+pub const MIN_DENOM_LENGTH: usize = 3;
+pub const MAX_DENOM_LENGTH: usize = 128;
+pub const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// first version with leases on name records
+pub const NAME_LEASE_VERSION: &str = "0.13.0";
+// first version with the owner index on name records
+pub const NAME_OWNER_INDEX_VERSION: &str = "0.15.0";
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+// expired names stay reserved for their owner to renew
+pub const GRACE_PERIOD_SECONDS: u64 = 30 * 24 * 60 * 60;
+pub const MAX_REGISTRATION_YEARS: u32 = 10;
+pub const AUCTION_DURATION_SECONDS: u64 = 3 * 24 * 60 * 60;
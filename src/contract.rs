@@ -1,183 +1,792 @@
-use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response,
-    StdError, StdResult,
-};
-use cw_storage_plus::KeyDeserialize;
-
-use crate::error::ContractError;
-
-use crate::execute::*;
-use crate::helper::{abi_decode_to_binary, abi_encode_string};
-use crate::msg::{
-    ConfigResponse, CustomQueryMsg, ExecuteMsg, InstantiateMsg, LoadStatesResponse, QueryMsg,
-    ResolveRecordResponse,
-};
-use crate::state::{
-    Config, PendingRequests, CONFIG, NAME_RESOLVER, NONCE, PENDING, REQUEST, RESULT,
-};
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    msg: InstantiateMsg,
-) -> Result<Response, StdError> {
-    let config = Config {
-        purchase_price: msg.purchase_price,
-        transfer_price: msg.transfer_price,
-    };
-    CONFIG.save(deps.storage, &config)?;
-
-    Ok(Response::default())
-}
-
-// there are 6 execute msgs in this contract
-// 4 of them are used for crosschain communication
-//  * IReceive: handle request packet from other chain
-//  * IAck: handle ack packet from other chain
-//  * ISend: send request packet to other chain
-//  * SetDappMetadata: set feepayer for this contract
-// 2 of them are used for debug
-//  * Register: register name for certain address
-//  * Transfer: transfer name to other address
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
-    match msg {
-        ExecuteMsg::IReceive {
-            src_chain_id,
-            request_sender,
-            packet,
-        } => execute_i_receive(deps, env, info, src_chain_id, request_sender, packet),
-        ExecuteMsg::IAck {
-            request_identifier,
-            exec_status,
-            exec_data,
-        } => execute_i_ack(deps, env, request_identifier, exec_status, exec_data),
-        ExecuteMsg::ISend {
-            version,
-            route_amount,
-            route_recipient,
-            dest_chain_id,
-            request_metadata,
-            gateway_address,
-            handler_address,
-            payload,
-        } => execute_i_send(
-            deps,
-            env,
-            version,
-            route_amount,
-            route_recipient,
-            dest_chain_id,
-            request_metadata,
-            gateway_address,
-            handler_address,
-            payload,
-        ),
-        ExecuteMsg::SetDappMetadata {
-            fee_payer_address,
-            gateway_address,
-        } => set_dapp_metadata(deps, fee_payer_address, gateway_address),
-        ExecuteMsg::Register { name } => execute_register(deps, env, info, name),
-        ExecuteMsg::Transfer { name, to } => execute_transfer(deps, env, info, name, to),
-    }
-}
-
-// there are 4 query msgs in this contract
-// 1 for crosschain communication
-//  * IQuery: handle request query packet(which isReadCall = true) from other chain
-// 3 for debug
-//  * ResolveRecord: returns the current address that the name resolves to
-//  * Config: returns urrent config
-//  * LoadStates: returns current state variables
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::IQuery { packet } => i_query(deps, env, packet),
-        QueryMsg::ResolveRecord { name } => query_resolver(deps, env, name),
-        QueryMsg::Config {} => to_binary::<ConfigResponse>(&CONFIG.load(deps.storage)?.into()),
-        QueryMsg::LoadStates {} => load_states(deps),
-    }
-}
-
-fn i_query(deps: Deps, env: Env, payload: Binary) -> StdResult<Binary> {
-    // first, decode query payload, which was encoded by request sender
-    let decoded = abi_decode_to_binary(&payload).or(Err(StdError::generic_err("abi_decode_error".to_string())))?;
-    let query_msg: CustomQueryMsg = from_binary(&decoded)?;
-
-    match query_msg {
-        CustomQueryMsg::Config {} => {
-            let config = CONFIG.load(deps.storage)?;
-
-            // abi encode result
-            let result = abi_encode_string(&format!("{:?}", config));
-            return Ok(to_binary(&result)?);
-        }
-        CustomQueryMsg::ResolveRecord { name } => {
-            let key = name.as_bytes();
-            let address = match NAME_RESOLVER.may_load(deps.storage, key)? {
-                Some(record) => Some(String::from(&record.owner)),
-                None => None,
-            };
-            let resp = ResolveRecordResponse { address };
-
-            // abi encode result
-            to_binary(&abi_encode_string(&format!("{:?}", resp)))
-        }
-    }
-}
-
-fn query_resolver(deps: Deps, _env: Env, name: String) -> StdResult<Binary> {
-    let key = name.as_bytes();
-
-    let address = match NAME_RESOLVER.may_load(deps.storage, key)? {
-        Some(record) => Some(String::from(&record.owner)),
-        None => None,
-    };
-    let resp = ResolveRecordResponse { address };
-
-    to_binary(&resp)
-}
-
-// this function is used for debug
-// it load all variabls from state store and return them
-fn load_states(deps: Deps) -> StdResult<Binary> {
-    let mut name_resolver: Vec<(String, String)> = vec![];
-    for item in NAME_RESOLVER.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
-        match item {
-            Ok((key, namerecord)) => {
-                let name = String::from_slice(&key)?;
-                let addresss = namerecord.owner.to_string();
-                name_resolver.push((name, addresss));
-            }
-            Err(_) => {
-                continue;
-            }
-        }
-    }
-
-    let request = REQUEST.load(deps.storage).unwrap_or(Binary::from(b"empty"));
-    let result = RESULT.load(deps.storage).unwrap_or(Binary::from(b"empty"));
-    let nonce = NONCE.load(deps.storage).unwrap_or(1000000000000000000);
-    let pending = PENDING
-        .load(deps.storage)
-        .unwrap_or(PendingRequests { requests: vec![] })
-        .requests;
-
-    let load_states_response = LoadStatesResponse {
-        name_resolver,
-        request,
-        result,
-        nonce,
-        pending,
-    };
-
-    let res = to_binary(&load_states_response)?;
-    Ok(res)
-}
+use cosmwasm_std::{
+    entry_point, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult,
+};
+use cw2::set_contract_version;
+use cw_storage_plus::{Bound, KeyDeserialize};
+
+use crate::error::ContractError;
+
+use crate::consts::{
+    CONTRACT_NAME, CONTRACT_VERSION, DEFAULT_LIMIT, DEFAULT_MAX_RETRY_ATTEMPTS, MAX_LIMIT,
+};
+use crate::execute::*;
+use crate::helper::{
+    abi_decode_to_binary, abi_encode_config, abi_encode_resolve_record, decode_request_metadata,
+    inbound_packet_key, normalize_sender, resolve_request_metadata, validate_price,
+};
+use crate::msg::{
+    AdminResponse, Approval, AuctionResponse, ChainResponse, ChainsResponse, ConfigResponse,
+    CreditsResponse, CustomQueryMsg, DappMetadataResponse, ExecuteMsg, FailedRequestResponse,
+    FailedRequestsResponse, InstantiateMsg, IsTrustedSenderResponse, LoadStatesResponse,
+    OutboundRequestResponse, OutboundRequestsResponse, PacketProcessedResponse, NameExtension,
+    NftInfoResponse, OwnerOfResponse, QueryMsg, ResolveRecordResponse, ReverseResolveResponse,
+    SubdomainsResponse, TokensResponse, ResolveResultResponse, RetryEntryResponse,
+    RetryQueueResponse, TrustedSendersResponse,
+};
+use crate::state::{
+    name_records, outbound_requests, ChainConfig, Config, NameRecord, Owner, ReplyFailurePolicy,
+    RequestStatus, ACK_RESULTS, ADMIN, APPROVALS, AUCTIONS, CHAINS, CONFIG, CREDITS, DAPP_METADATA,
+    DEBUG_STATE, FAILED_REQUESTS, NAME_TARGETS, PRIMARY_NAMES, SUBDOMAINS, NONCE, PENDING_ADMIN,
+    PROCESSED_PACKETS, RETRY_QUEUE, TRUSTED_SENDERS,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, StdError> {
+    let invalid_price = |err: ContractError| StdError::generic_err(err.to_string());
+    validate_price(&msg.purchase_price).map_err(invalid_price)?;
+    validate_price(&msg.transfer_price).map_err(invalid_price)?;
+
+    let config = Config {
+        purchase_price: msg.purchase_price,
+        transfer_price: msg.transfer_price,
+        gateway_address: deps.api.addr_validate(&msg.gateway_address)?,
+        reply_failure_policy: msg.reply_failure_policy.unwrap_or(ReplyFailurePolicy::Strict),
+        max_retry_attempts: msg.max_retry_attempts.unwrap_or(DEFAULT_MAX_RETRY_ATTEMPTS),
+    };
+    CONFIG.save(deps.storage, &config)?;
+    let admin = match msg.admin {
+        Some(admin) => deps.api.addr_validate(&admin)?,
+        None => info.sender,
+    };
+    ADMIN.save(deps.storage, &admin)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+// there are 34 execute msgs in this contract
+// 8 of them are used for crosschain communication
+//  * IReceive: handle request packet from other chain
+//  * HandleIReceive: run the handler of a request packet, only called by this contract
+//  * IAck: handle ack packet from other chain
+//  * ISend: send request packet to other chain
+//  * ISendBatch: send request packets to several chains
+//  * ISendTo: send request packet to a registered chain
+//  * Retry: resend failed request packets
+//  * SetDappMetadata: set feepayer for this contract
+// 11 of them are used for debug
+//  * Register: register name for certain address
+//  * Renew: extend the lease of a name
+//  * Transfer: transfer name to other address
+//  * SetTarget: set the address a name resolves to on a chain
+//  * RemoveTarget: remove the address a name resolves to on a chain
+//  * CreateSubdomain: create a subdomain of an owned name
+//  * ReclaimSubdomain: take a subdomain back from its owner and remove it
+//  * SetPrimaryName: set the name an address reverse resolves to
+//  * StartAuction: auction a name nobody holds
+//  * Bid: bid on an auctioned name
+//  * SettleAuction: hand an auctioned name to the highest bidder
+// 4 of them implement cw721, the token id is the name
+//  * TransferNft: transfer a name to a local address
+//  * SendNft: transfer a name to a contract and call its ReceiveNft hook
+//  * Approve: let another address transfer a name
+//  * Revoke: withdraw an approval
+// 11 of them are used for administration
+//  * ProposeAdmin: propose a new admin
+//  * AcceptAdmin: take over the proposed admin role
+//  * RenounceAdmin: give up the admin role
+//  * UpdateConfig: change purchase and transfer prices
+//  * UpdateGateway: rotate the trusted router gateway address
+//  * SetReplyFailurePolicy: fail or record failed gateway ISend submessages
+//  * SetMaxRetryAttempts: limit how often a failed request can be retried
+//  * SetChain: register the handler and metadata defaults of a destination chain
+//  * RemoveChain: remove a destination chain from the registry
+//  * AddTrustedSender: allowlist a remote handler contract
+//  * RemoveTrustedSender: remove a remote handler contract from the allowlist
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::IReceive {
+            src_chain_id,
+            request_sender,
+            packet,
+            request_identifier,
+        } => execute_i_receive(
+            deps,
+            env,
+            info,
+            src_chain_id,
+            request_sender,
+            packet,
+            request_identifier,
+        ),
+        ExecuteMsg::HandleIReceive { caller, payload } => {
+            execute_handle_i_receive(deps, env, info, caller, payload)
+        }
+        ExecuteMsg::IAck {
+            request_identifier,
+            exec_status,
+            exec_data,
+        } => execute_i_ack(deps, env, info, request_identifier, exec_status, exec_data),
+        ExecuteMsg::ISend {
+            version,
+            route_amount,
+            route_recipient,
+            dest_chain_id,
+            request_metadata,
+            metadata,
+            handler_address,
+            payload,
+        } => execute_i_send(
+            deps,
+            env,
+            info,
+            version,
+            route_amount,
+            route_recipient,
+            dest_chain_id,
+            resolve_request_metadata(request_metadata, metadata)?,
+            handler_address,
+            payload,
+        ),
+        ExecuteMsg::ISendBatch {
+            requests,
+            allow_partial_failure,
+        } => execute_i_send_batch(deps, env, info, requests, allow_partial_failure),
+        ExecuteMsg::ISendTo {
+            dest_chain_id,
+            payload,
+            handler_address,
+            metadata,
+            route_amount,
+            route_recipient,
+        } => execute_i_send_to(
+            deps,
+            env,
+            info,
+            dest_chain_id,
+            payload,
+            handler_address,
+            metadata,
+            route_amount,
+            route_recipient,
+        ),
+        ExecuteMsg::Retry { ids, metadata } => execute_retry(deps, env, info, ids, metadata),
+        ExecuteMsg::SetDappMetadata { fee_payer_address } => {
+            set_dapp_metadata(deps, info, fee_payer_address)
+        }
+        ExecuteMsg::Register { name, years } => {
+            let owner = Owner::local(&env, &info.sender);
+            execute_register(deps, env, info, owner, name, years.unwrap_or(1))
+        }
+        ExecuteMsg::Renew { name, years } => execute_renew(deps, env, info, name, years),
+        ExecuteMsg::Transfer {
+            name,
+            to,
+            to_chain_id,
+        } => {
+            let caller = Owner::local(&env, &info.sender);
+            execute_transfer(deps, env, info, caller, name, to, to_chain_id)
+        }
+        ExecuteMsg::SetTarget {
+            name,
+            chain_id,
+            address,
+        } => {
+            let caller = Owner::local(&env, &info.sender);
+            execute_set_target(deps, env, caller, name, chain_id, address)
+        }
+        ExecuteMsg::RemoveTarget { name, chain_id } => {
+            let caller = Owner::local(&env, &info.sender);
+            execute_remove_target(deps, env, caller, name, chain_id)
+        }
+        ExecuteMsg::CreateSubdomain {
+            parent,
+            label,
+            owner,
+            owner_chain_id,
+        } => {
+            let caller = Owner::local(&env, &info.sender);
+            execute_create_subdomain(deps, env, caller, parent, label, owner, owner_chain_id)
+        }
+        ExecuteMsg::ReclaimSubdomain { name } => {
+            let caller = Owner::local(&env, &info.sender);
+            execute_reclaim_subdomain(deps, env, caller, name)
+        }
+        ExecuteMsg::SetPrimaryName { name } => {
+            let caller = Owner::local(&env, &info.sender);
+            execute_set_primary_name(deps, env, caller, name)
+        }
+        ExecuteMsg::TransferNft {
+            recipient,
+            token_id,
+        } => execute_transfer_nft(deps, env, info, recipient, token_id),
+        ExecuteMsg::SendNft {
+            contract,
+            token_id,
+            msg,
+        } => execute_send_nft(deps, env, info, contract, token_id, msg),
+        ExecuteMsg::Approve {
+            spender,
+            token_id,
+            expires,
+        } => execute_approve(deps, env, info, spender, token_id, expires),
+        ExecuteMsg::Revoke { spender, token_id } => {
+            execute_revoke(deps, env, info, spender, token_id)
+        }
+        ExecuteMsg::StartAuction { name, min_bid } => {
+            execute_start_auction(deps, env, info, name, min_bid)
+        }
+        ExecuteMsg::Bid { name } => {
+            let bidder = Owner::local(&env, &info.sender);
+            execute_bid(deps, env, info, bidder, name)
+        }
+        ExecuteMsg::SettleAuction { name } => execute_settle_auction(deps, env, name),
+        ExecuteMsg::ProposeAdmin { admin } => execute_propose_admin(deps, info, admin),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
+        ExecuteMsg::RenounceAdmin {} => execute_renounce_admin(deps, info),
+        ExecuteMsg::UpdateConfig {
+            purchase_price,
+            transfer_price,
+        } => execute_update_config(deps, info, purchase_price, transfer_price),
+        ExecuteMsg::UpdateGateway { gateway_address } => {
+            execute_update_gateway(deps, info, gateway_address)
+        }
+        ExecuteMsg::SetReplyFailurePolicy { policy } => {
+            execute_set_reply_failure_policy(deps, info, policy)
+        }
+        ExecuteMsg::SetMaxRetryAttempts { max_retry_attempts } => {
+            execute_set_max_retry_attempts(deps, info, max_retry_attempts)
+        }
+        ExecuteMsg::SetChain {
+            dest_chain_id,
+            chain_type,
+            handler_address,
+            metadata,
+        } => execute_set_chain(deps, info, dest_chain_id, chain_type, handler_address, metadata),
+        ExecuteMsg::RemoveChain { dest_chain_id } => {
+            execute_remove_chain(deps, info, dest_chain_id)
+        }
+        ExecuteMsg::AddTrustedSender { chain_id, sender } => {
+            execute_add_trusted_sender(deps, info, chain_id, sender)
+        }
+        ExecuteMsg::RemoveTrustedSender { chain_id, sender } => {
+            execute_remove_trusted_sender(deps, info, chain_id, sender)
+        }
+    }
+}
+
+// there are 29 query msgs in this contract
+// 3 for crosschain communication
+//  * IQuery: handle request query packet(which isReadCall = true) from other chain
+//  * PacketProcessed: checks whether an inbound packet has already been executed
+//  * DappMetadata: returns the fee payer configured on the gateway
+// 7 for debug
+//  * ResolveRecord: returns the current owner of the name, or its target address on a chain
+//  * ReverseResolve: returns the primary name of an address
+//  * Subdomains: lists the subdomains of a name
+//  * Config: returns urrent config
+//  * LoadStates: returns current state variables
+//  * Auction: returns the state of a name auction
+//  * Credits: returns refunds a remote account can claim
+// 4 for cw721
+//  * OwnerOf: returns the owner and approvals of a name
+//  * NftInfo: returns the lease of a name
+//  * Tokens: lists the names of a local owner
+//  * AllTokens: lists all names
+// 5 for administration
+//  * Admin: returns the current and proposed admin
+//  * TrustedSenders: lists allowlisted remote handler contracts of a chain
+//  * IsTrustedSender: checks whether a remote handler contract is allowlisted
+//  * Chain: returns the registry entry of a destination chain
+//  * Chains: lists registered destination chains
+// 10 for tracking outbound requests
+//  * OutboundRequest: returns a request sent through ISend
+//  * OutboundRequestsByStatus: lists sent requests with a given status
+//  * OutboundRequestsByChain: lists sent requests to a given destination chain
+//  * RequestResult: returns the ack result of a sent request
+//  * OutboundRequestMetadata: returns the decoded metadata of a sent request
+//  * DecodeRequestMetadata: decodes packed request metadata
+//  * FailedRequest: returns a request the gateway rejected
+//  * FailedRequests: lists requests the gateway rejected
+//  * RetryEntry: returns a failed request waiting to be retried
+//  * RetryQueue: lists failed requests waiting to be retried
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::IQuery { packet } => i_query(deps, env, packet),
+        QueryMsg::ResolveRecord { name, chain_id } => query_resolver(deps, env, name, chain_id),
+        QueryMsg::OwnerOf {
+            token_id,
+            include_expired,
+        } => query_owner_of(deps, env, token_id, include_expired.unwrap_or(false)),
+        QueryMsg::NftInfo { token_id } => {
+            let record = load_token(deps, &env, &token_id)?;
+            to_binary(&NftInfoResponse {
+                token_uri: None,
+                extension: NameExtension {
+                    owner_chain_id: record.owner.chain_id,
+                    expires: record.expires,
+                },
+            })
+        }
+        QueryMsg::Tokens {
+            owner,
+            start_after,
+            limit,
+        } => query_tokens(deps, env, owner, start_after, limit),
+        QueryMsg::AllTokens { start_after, limit } => {
+            query_all_tokens(deps, env, start_after, limit)
+        }
+        QueryMsg::Subdomains {
+            parent,
+            start_after,
+            limit,
+        } => query_subdomains(deps, parent, start_after, limit),
+        QueryMsg::ReverseResolve { address, chain_id } => {
+            let name = primary_name(deps, &env, &address, chain_id)?;
+            to_binary(&ReverseResolveResponse { name })
+        }
+        QueryMsg::Config {} => to_binary::<ConfigResponse>(&CONFIG.load(deps.storage)?.into()),
+        QueryMsg::LoadStates {} => load_states(deps),
+        QueryMsg::Admin {} => to_binary(&AdminResponse {
+            admin: ADMIN.may_load(deps.storage)?.map(String::from),
+            pending_admin: PENDING_ADMIN.may_load(deps.storage)?.map(String::from),
+        }),
+        QueryMsg::TrustedSenders {
+            chain_id,
+            start_after,
+            limit,
+        } => query_trusted_senders(deps, chain_id, start_after, limit),
+        QueryMsg::IsTrustedSender { chain_id, sender } => {
+            let sender = normalize_sender(&sender);
+            let trusted = TRUSTED_SENDERS.has(deps.storage, (chain_id.as_str(), sender.as_str()));
+            to_binary(&IsTrustedSenderResponse { trusted })
+        }
+        QueryMsg::Auction { name } => query_auction(deps, env, name),
+        QueryMsg::Credits { chain_id, address } => {
+            let address = normalize_sender(&address);
+            let credits = CREDITS
+                .may_load(deps.storage, (chain_id.as_str(), address.as_str()))?
+                .unwrap_or_default();
+            to_binary(&CreditsResponse { credits })
+        }
+        QueryMsg::Chain { dest_chain_id } => {
+            let chain = CHAINS.load(deps.storage, &dest_chain_id)?;
+            to_binary(&chain_response(dest_chain_id, chain)?)
+        }
+        QueryMsg::Chains { start_after, limit } => query_chains(deps, start_after, limit),
+        QueryMsg::OutboundRequest { request_identifier } => {
+            let request = outbound_requests().load(deps.storage, request_identifier)?;
+            to_binary(&OutboundRequestResponse::from((request_identifier, request)))
+        }
+        QueryMsg::OutboundRequestsByStatus {
+            status,
+            start_after,
+            limit,
+        } => query_outbound_requests_by_status(deps, status, start_after, limit),
+        QueryMsg::OutboundRequestsByChain {
+            dest_chain_id,
+            start_after,
+            limit,
+        } => query_outbound_requests_by_chain(deps, dest_chain_id, start_after, limit),
+        QueryMsg::RequestResult { request_identifier } => {
+            let ack_result = ACK_RESULTS.may_load(deps.storage, request_identifier)?;
+            let resp = ResolveResultResponse {
+                request_identifier,
+                exec_status: ack_result.as_ref().map(|ack_result| ack_result.exec_status),
+                result: ack_result.map(|ack_result| ack_result.exec_data),
+            };
+            to_binary(&resp)
+        }
+        QueryMsg::OutboundRequestMetadata { request_identifier } => {
+            let request = outbound_requests().load(deps.storage, request_identifier)?;
+            let metadata = decode_request_metadata(&request.request_metadata)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            to_binary(&metadata)
+        }
+        QueryMsg::DecodeRequestMetadata { request_metadata } => {
+            let metadata = decode_request_metadata(&request_metadata)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            to_binary(&metadata)
+        }
+        QueryMsg::FailedRequest { id } => {
+            let failed = FAILED_REQUESTS.load(deps.storage, id)?;
+            to_binary(&FailedRequestResponse::from((id, failed)))
+        }
+        QueryMsg::FailedRequests { start_after, limit } => {
+            let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+            let start = start_after.map(Bound::exclusive);
+            let requests = FAILED_REQUESTS
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| item.map(FailedRequestResponse::from))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&FailedRequestsResponse { requests })
+        }
+        QueryMsg::RetryEntry { id } => {
+            let entry = RETRY_QUEUE.load(deps.storage, id)?;
+            to_binary(&RetryEntryResponse::from((id, entry)))
+        }
+        QueryMsg::RetryQueue { start_after, limit } => {
+            let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+            let start = start_after.map(Bound::exclusive);
+            let entries = RETRY_QUEUE
+                .range(deps.storage, start, None, Order::Ascending)
+                .take(limit)
+                .map(|item| item.map(RetryEntryResponse::from))
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&RetryQueueResponse { entries })
+        }
+        QueryMsg::DappMetadata {} => {
+            let dapp_metadata = DAPP_METADATA.may_load(deps.storage)?;
+            to_binary(&DappMetadataResponse::from(dapp_metadata))
+        }
+        QueryMsg::PacketProcessed {
+            src_chain_id,
+            request_sender,
+            packet,
+            request_identifier,
+        } => {
+            let packet_key =
+                inbound_packet_key(&src_chain_id, &request_sender, &packet, request_identifier);
+            let height = PROCESSED_PACKETS.may_load(deps.storage, &packet_key)?;
+            to_binary(&PacketProcessedResponse {
+                processed: height.is_some(),
+                height,
+            })
+        }
+    }
+}
+
+fn i_query(deps: Deps, env: Env, payload: Binary) -> StdResult<Binary> {
+    // first, decode query payload, which was encoded by request sender
+    let decoded = abi_decode_to_binary(&payload).or(Err(StdError::generic_err("abi_decode_error".to_string())))?;
+    let query_msg: CustomQueryMsg = from_binary(&decoded)?;
+
+    // results are abi encoded tuples, so that solidity callers can abi.decode them
+    match query_msg {
+        CustomQueryMsg::ReverseResolve { address, chain_id } => {
+            // same (bool found, string) tuple as ResolveRecord
+            let result = match primary_name(deps, &env, &address, chain_id)? {
+                Some(name) => abi_encode_resolve_record(true, &name),
+                None => abi_encode_resolve_record(false, ""),
+            };
+            to_binary(&result)
+        }
+        CustomQueryMsg::Config {} => {
+            let config = CONFIG.load(deps.storage)?;
+            to_binary(&abi_encode_config(&config))
+        }
+        CustomQueryMsg::ResolveRecord { name, chain_id } => {
+            let key = name.as_bytes();
+            let record = name_records()
+                .may_load(deps.storage, key)?
+                .filter(|record| !record.is_expired(&env));
+            let address = match (record, chain_id) {
+                (Some(_), Some(chain_id)) => NAME_TARGETS.may_load(deps.storage, (key, &chain_id))?,
+                (Some(record), None) => Some(record.owner.address),
+                (None, _) => None,
+            };
+            let result = match address {
+                Some(address) => abi_encode_resolve_record(true, &address),
+                None => abi_encode_resolve_record(false, ""),
+            };
+            to_binary(&result)
+        }
+    }
+}
+
+fn query_resolver(
+    deps: Deps,
+    env: Env,
+    name: String,
+    chain_id: Option<String>,
+) -> StdResult<Binary> {
+    let key = name.as_bytes();
+
+    // expired names don't resolve
+    let record = name_records()
+        .may_load(deps.storage, key)?
+        .filter(|record| !record.is_expired(&env));
+    let target = match (&record, chain_id) {
+        (Some(_), Some(chain_id)) => NAME_TARGETS.may_load(deps.storage, (key, &chain_id))?,
+        _ => None,
+    };
+    let resp = ResolveRecordResponse {
+        target,
+        address: record.as_ref().map(|record| record.owner.address.clone()),
+        chain_id: record.as_ref().map(|record| record.owner.chain_id.clone()),
+        expires: record.map(|record| record.expires),
+    };
+
+    to_binary(&resp)
+}
+
+// the primary name only counts while its owner holds the name
+fn primary_name(
+    deps: Deps,
+    env: &Env,
+    address: &str,
+    chain_id: Option<String>,
+) -> StdResult<Option<String>> {
+    let owner = Owner {
+        chain_id: chain_id.unwrap_or_else(|| env.block.chain_id.clone()),
+        address: normalize_sender(address),
+    };
+    let key = (owner.chain_id.as_str(), owner.address.as_str());
+    let name = match PRIMARY_NAMES.may_load(deps.storage, key)? {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let record = name_records().may_load(deps.storage, name.as_bytes())?;
+    Ok(record
+        .filter(|record| record.owner == owner && !record.is_expired(env))
+        .map(|_| name))
+}
+
+// names stop being tokens when they expire, like they stop resolving
+fn load_token(deps: Deps, env: &Env, token_id: &str) -> StdResult<NameRecord> {
+    name_records()
+        .may_load(deps.storage, token_id.as_bytes())?
+        .filter(|record| !record.is_expired(env))
+        .ok_or_else(|| StdError::not_found(format!("token {}", token_id)))
+}
+
+fn query_owner_of(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: bool,
+) -> StdResult<Binary> {
+    let record = load_token(deps, &env, &token_id)?;
+    let approvals = APPROVALS
+        .prefix(token_id.as_bytes())
+        .range(deps.storage, None, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, expires)) => include_expired || !expires.is_expired(&env.block),
+            Err(_) => true,
+        })
+        .map(|item| item.map(|(spender, expires)| Approval { spender, expires }))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&OwnerOfResponse {
+        owner: record.owner.address,
+        owner_chain_id: record.owner.chain_id,
+        approvals,
+    })
+}
+
+fn query_tokens(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|name| Bound::exclusive(name.into_bytes()));
+    let owner = (env.block.chain_id.clone(), owner);
+
+    let tokens = name_records()
+        .idx
+        .owner
+        .prefix(owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .filter(|item| match item {
+            Ok((_, record)) => !record.is_expired(&env),
+            Err(_) => true,
+        })
+        .map(|item| item.and_then(|(name, _)| String::from_vec(name)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&TokensResponse { tokens })
+}
+
+fn query_all_tokens(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(|name| Bound::exclusive(name.as_bytes()));
+
+    let tokens = name_records()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .filter(|item| match item {
+            Ok((_, record)) => !record.is_expired(&env),
+            Err(_) => true,
+        })
+        .map(|item| item.and_then(|(name, _)| String::from_vec(name)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&TokensResponse { tokens })
+}
+
+fn query_subdomains(
+    deps: Deps,
+    parent: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let subdomains = SUBDOMAINS
+        .prefix(parent.as_str())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|label| label.map(|label| format!("{}.{}", label, parent)))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    to_binary(&SubdomainsResponse { subdomains })
+}
+
+fn query_auction(deps: Deps, env: Env, name: String) -> StdResult<Binary> {
+    let auction = AUCTIONS.load(deps.storage, name.as_bytes())?;
+    let highest_bid = auction.highest_bid;
+    let resp = AuctionResponse {
+        name,
+        min_bid: auction.min_bid,
+        end: auction.end,
+        ended: env.block.time >= auction.end,
+        highest_bidder: highest_bid.as_ref().map(|bid| bid.bidder.address.clone()),
+        highest_bidder_chain_id: highest_bid.as_ref().map(|bid| bid.bidder.chain_id.clone()),
+        highest_bid: highest_bid.map(|bid| bid.amount),
+    };
+
+    to_binary(&resp)
+}
+
+fn query_trusted_senders(
+    deps: Deps,
+    chain_id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|sender| normalize_sender(&sender));
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let senders = TRUSTED_SENDERS
+        .prefix(chain_id.as_str())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<String>>>()?;
+
+    to_binary(&TrustedSendersResponse { chain_id, senders })
+}
+
+fn chain_response(dest_chain_id: String, chain: ChainConfig) -> StdResult<ChainResponse> {
+    let metadata = decode_request_metadata(&chain.request_metadata)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(ChainResponse {
+        dest_chain_id,
+        chain_type: chain.chain_type,
+        handler_address: chain.handler_address,
+        metadata,
+    })
+}
+
+fn query_chains(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let chains = CHAINS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(dest_chain_id, chain)| chain_response(dest_chain_id, chain)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ChainsResponse { chains })
+}
+
+fn query_outbound_requests_by_status(
+    deps: Deps,
+    status: RequestStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let requests = outbound_requests()
+        .idx
+        .status
+        .prefix(status.as_str().to_string())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(OutboundRequestResponse::from))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&OutboundRequestsResponse { requests })
+}
+
+fn query_outbound_requests_by_chain(
+    deps: Deps,
+    dest_chain_id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let requests = outbound_requests()
+        .idx
+        .dest_chain_id
+        .prefix(dest_chain_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(OutboundRequestResponse::from))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&OutboundRequestsResponse { requests })
+}
+
+// this function is used for debug
+// it load all variabls from state store and return them
+fn load_states(deps: Deps) -> StdResult<Binary> {
+    let mut name_resolver: Vec<(String, String)> = vec![];
+    for item in name_records().range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+        match item {
+            Ok((key, namerecord)) => {
+                let name = String::from_slice(&key)?;
+                let addresss = namerecord.owner.address;
+                name_resolver.push((name, addresss));
+            }
+            Err(_) => {
+                continue;
+            }
+        }
+    }
+
+    let debug_state = DEBUG_STATE.may_load(deps.storage)?.unwrap_or_default();
+    let request = debug_state.request.unwrap_or(Binary::from(b"empty"));
+    let result = debug_state.result.unwrap_or(Binary::from(b"empty"));
+    let nonce = NONCE.load(deps.storage).unwrap_or(1000000000000000000);
+    let pending = outbound_requests()
+        .idx
+        .status
+        .prefix(RequestStatus::Pending.as_str().to_string())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+
+    let load_states_response = LoadStatesResponse {
+        name_resolver,
+        request,
+        result,
+        nonce,
+        pending,
+    };
+
+    let res = to_binary(&load_states_response)?;
+    Ok(res)
+}
@@ -1,29 +1,154 @@
-use cosmwasm_std::StdError;
-use thiserror::Error;
-
-#[derive(Error, Debug)]
-pub enum ContractError {
-    #[error("{0}")]
-    Std(#[from] StdError),
-
-    #[error("Unauthorized")]
-    Unauthorized {},
-
-    #[error("Insufficient funds sent")]
-    InsufficientFundsSend {},
-
-    #[error("Name does not exist (name {name})")]
-    NameNotExists { name: String },
-
-    #[error("Name has been taken (name {name})")]
-    NameTaken { name: String },
-
-    #[error("Name too short (length {length} min_length {min_length})")]
-    NameTooShort { length: u64, min_length: u64 },
-
-    #[error("Name too long (length {length} min_length {max_length})")]
-    NameTooLong { length: u64, max_length: u64 },
-
-    #[error("Invalid character(char {c}")]
-    InvalidCharacter { c: char },
-}
+use cosmwasm_std::StdError;
+use cw_utils::PaymentError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Sender is not the admin")]
+    NotAdmin {},
+
+    #[error("Sender is not the pending admin")]
+    NotPendingAdmin {},
+
+    #[error("Sender is not the router gateway (sender {sender})")]
+    UnauthorizedGateway { sender: String },
+
+    #[error("Inbound packet has already been processed")]
+    PacketAlreadyProcessed {},
+
+    #[error("Invalid request metadata ({reason})")]
+    InvalidRequestMetadata { reason: String },
+
+    #[error("Invalid handler address {address}")]
+    InvalidHandlerAddress { address: String },
+
+    #[error("Invalid address {address}")]
+    InvalidAddress { address: String },
+
+    #[error("Only the owner of {parent} can manage its subdomains")]
+    NotParentOwner { parent: String },
+
+    #[error("Approval has already expired")]
+    ApprovalExpired {},
+
+    #[error("Invalid migration: {reason}")]
+    InvalidMigration { reason: String },
+
+    #[error("Cannot downgrade from {previous_version} to {new_version}")]
+    CannotDowngrade {
+        previous_version: String,
+        new_version: String,
+    },
+
+    #[error("Invalid registration years {years} (min 1 max {max})")]
+    InvalidYears { years: u32, max: u32 },
+
+    #[error("Name {name} has expired")]
+    NameExpired { name: String },
+
+    #[error("Name {name} is being auctioned")]
+    AuctionInProgress { name: String },
+
+    #[error("No auction for name {name}")]
+    AuctionNotFound { name: String },
+
+    #[error("Auction for name {name} has ended")]
+    AuctionEnded { name: String },
+
+    #[error("Auction for name {name} has not ended yet")]
+    AuctionNotEnded { name: String },
+
+    #[error("Bid too low (minimum {minimum})")]
+    BidTooLow { minimum: String },
+
+    #[error("No credits to claim")]
+    NoCredits {},
+
+    #[error("Invalid denom {denom}")]
+    InvalidDenom { denom: String },
+
+    #[error("Chain {dest_chain_id} is not registered")]
+    ChainNotRegistered { dest_chain_id: String },
+
+    #[error("Invalid batch size (got {size} max {max})")]
+    InvalidBatchSize { size: u64, max: u64 },
+
+    #[error("Retry limit reached (id {id} max_attempts {max_attempts})")]
+    RetryLimitReached { id: u64, max_attempts: u32 },
+
+    #[error("Request has already been acknowledged (request_identifier {request_identifier})")]
+    RequestAlreadyAcked { request_identifier: u64 },
+
+    #[error("Remote sender is not trusted (chain_id {chain_id} sender {sender})")]
+    UntrustedSender { chain_id: String, sender: String },
+
+    #[error("Insufficient funds sent")]
+    InsufficientFundsSend {},
+
+    #[error("Name does not exist (name {name})")]
+    NameNotExists { name: String },
+
+    #[error("Name has been taken (name {name})")]
+    NameTaken { name: String },
+
+    #[error("Name too short (length {length} min_length {min_length})")]
+    NameTooShort { length: u64, min_length: u64 },
+
+    #[error("Name too long (length {length} min_length {max_length})")]
+    NameTooLong { length: u64, max_length: u64 },
+
+    #[error("Invalid character(char {c}")]
+    InvalidCharacter { c: char },
+}
+
+impl ContractError {
+    /// code is the stable numeric reason reported to remote dapps in failure acks,
+    /// 0 is reserved for success and codes must never be reused
+    pub fn code(&self) -> u32 {
+        match self {
+            ContractError::Std(_) => 1,
+            ContractError::Unauthorized {} => 2,
+            ContractError::UnauthorizedGateway { .. } => 3,
+            ContractError::PacketAlreadyProcessed {} => 4,
+            ContractError::InvalidRequestMetadata { .. } => 5,
+            ContractError::RequestAlreadyAcked { .. } => 6,
+            ContractError::UntrustedSender { .. } => 7,
+            ContractError::InsufficientFundsSend {} => 8,
+            ContractError::NameNotExists { .. } => 9,
+            ContractError::NameTaken { .. } => 10,
+            ContractError::NameTooShort { .. } => 11,
+            ContractError::NameTooLong { .. } => 12,
+            ContractError::InvalidCharacter { .. } => 13,
+            ContractError::RetryLimitReached { .. } => 14,
+            ContractError::InvalidBatchSize { .. } => 15,
+            ContractError::InvalidHandlerAddress { .. } => 16,
+            ContractError::ChainNotRegistered { .. } => 17,
+            ContractError::NotAdmin {} => 18,
+            ContractError::NotPendingAdmin {} => 19,
+            ContractError::InvalidDenom { .. } => 20,
+            ContractError::InvalidMigration { .. } => 21,
+            ContractError::CannotDowngrade { .. } => 22,
+            ContractError::InvalidYears { .. } => 23,
+            ContractError::NameExpired { .. } => 24,
+            ContractError::AuctionInProgress { .. } => 25,
+            ContractError::AuctionNotFound { .. } => 26,
+            ContractError::AuctionEnded { .. } => 27,
+            ContractError::AuctionNotEnded { .. } => 28,
+            ContractError::BidTooLow { .. } => 29,
+            ContractError::Payment(_) => 30,
+            ContractError::NoCredits {} => 31,
+            ContractError::InvalidAddress { .. } => 32,
+            ContractError::NotParentOwner { .. } => 33,
+            ContractError::ApprovalExpired {} => 34,
+        }
+    }
+}
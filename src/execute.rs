@@ -1,190 +1,1280 @@
 use cosmwasm_std::{
-    from_binary, wasm_execute, Binary, DepsMut, Env, Event, 
-    MessageInfo, ReplyOn, Response, StdError, SubMsg, to_binary,
+    from_binary, wasm_execute, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, ReplyOn, Response, StdResult, Storage, SubMsg, Uint128,
 };
-use router_wasm_bindings::ethabi::Contract;
+use cw_utils::{must_pay, nonpayable, Expiration};
 
 use crate::error::ContractError;
 use crate::helper::{
-    abi_decode_to_binary, abi_encode_string, assert_sent_sufficient_coin, get_request_packet,
-    validate_name,
+    abi_decode_to_binary, abi_encode_string, assert_admin, assert_gateway,
+    assert_sent_sufficient_coin, assert_trusted_sender, decode_request_metadata,
+    encode_request_metadata, get_request_packet, inbound_packet_key, lease_price, normalize_sender,
+    parent_name, resolve_request_metadata, DryRunStorage, save_debug_request, save_debug_result,
+    sha256, validate_address, validate_handler_address, validate_label, validate_name,
+    validate_price,
 };
 use crate::msg::{
-    ExecuteMsg, GatewayMsg, CustomExecuteMsg,
+    Cw721ReceiveMsg, ExecuteMsg, GatewayMsg, CustomExecuteMsg, ReceiverExecuteMsg, ISendRequest,
+    RequestMetadata,
+};
+use crate::state::{
+    name_records, outbound_requests, AckResult, Auction, Bid, ChainConfig, ChainType, NameRecord,
+    OutboundRequest, Owner, PendingBatch, PendingIReceive, PENDING_I_RECEIVE, ReplyFailurePolicy,
+    RequestStatus, RetryEntry, RetrySource, ACK_RESULTS, ADMIN, CONFIG, PENDING_ADMIN, APPROVALS,
+    AUCTIONS, CHAINS, CREDITS, NAME_TARGETS, PRIMARY_NAMES, SUBDOMAINS, PENDING_BATCH,
+    PENDING_FEE_PAYER, PENDING_SENDS, PROCESSED_PACKETS, RETRY_QUEUE, RETRY_SEQ, TRUSTED_SENDERS,
 };
-use crate::state::{NameRecord, CONFIG, NAME_RESOLVER, REQUEST, RESULT};
 
-use crate::consts::ISEND_ID;
+use crate::consts::{
+    AUCTION_DURATION_SECONDS, DEFAULT_ISEND_VERSION, ISEND_ID, I_RECEIVE_ID, MAX_BATCH_SIZE,
+    SECONDS_PER_YEAR, SET_DAPP_METADATA_ID,
+};
 
 pub fn execute_register(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
+    owner: Owner,
     name: String,
+    years: u32,
 ) -> Result<Response, ContractError> {
     // we only need to check here - at point of registration
     validate_name(&name)?;
+    if let Some(parent) = parent_name(&name) {
+        return Err(ContractError::NotParentOwner {
+            parent: parent.to_string(),
+        });
+    }
     let config = CONFIG.load(deps.storage)?;
-    assert_sent_sufficient_coin(&info.funds, config.purchase_price)?;
+    assert_sent_sufficient_coin(&info.funds, lease_price(config.purchase_price, years)?)?;
 
     let key = name.as_bytes();
     let record = NameRecord {
-        owner: info.sender.clone(),
+        owner: owner.clone(),
+        expires: env.block.time.plus_seconds(SECONDS_PER_YEAR * years as u64),
     };
 
-    if (NAME_RESOLVER.may_load(deps.storage, key)?).is_some() {
-        // name is already taken
-        return Err(ContractError::NameTaken { name });
+    let previous = name_records().may_load(deps.storage, key)?;
+    if let Some(current) = &previous {
+        // an expired name stays reserved for its owner until the grace period is over
+        if !current.is_released(&env) {
+            return Err(ContractError::NameTaken { name });
+        }
+    }
+    if let Some(auction) = AUCTIONS.may_load(deps.storage, key)? {
+        // an ended auction nobody bid on holds nothing to settle
+        if auction.highest_bid.is_some() || env.block.time < auction.end {
+            return Err(ContractError::AuctionInProgress { name });
+        }
+        AUCTIONS.remove(deps.storage, key);
     }
 
     // name is available
-    NAME_RESOLVER.save(deps.storage, key, &record)?;
+    name_records().save(deps.storage, key, &record)?;
+    clear_owner_records(deps.storage, key)?;
+    if let Some(previous) = previous {
+        clear_primary_name(deps.storage, &previous.owner, &name)?;
+        remove_subdomains(deps.storage, &name)?;
+    }
     let result_txt = format!(
-        "execute_register, name: {}, owner: {}",
+        "execute_register, name: {}, owner: {}, chain_id: {}",
         name,
-        info.sender.to_string()
+        owner.address,
+        owner.chain_id
     );
     let result = abi_encode_string(&result_txt);
-    RESULT.save(deps.storage, &result)?;
+    save_debug_result(deps.storage, &result)?;
     let response = Response::new().set_data(result);
     Ok(response)
 }
 
 pub fn execute_transfer(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
+    caller: Owner,
     name: String,
     to: String,
+    to_chain_id: Option<String>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     assert_sent_sufficient_coin(&info.funds, config.transfer_price)?;
 
-    let new_owner = deps.api.addr_validate(&to)?;
+    let new_owner = new_owner(deps.as_ref(), &env, &caller, to, to_chain_id)?;
     let key = name.as_bytes();
-    NAME_RESOLVER.update(deps.storage, key, |record| {
-        if let Some(mut record) = record {
-            if info.sender != record.owner {
-                return Err(ContractError::Unauthorized {});
+    let mut record = name_records()
+        .may_load(deps.storage, key)?
+        .ok_or_else(|| ContractError::NameNotExists { name: name.clone() })?;
+    // cw721 spenders approved by the owner may move the name as well
+    if caller != record.owner && !is_approved(deps.storage, &env, key, &caller)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if record.is_expired(&env) {
+        return Err(ContractError::NameExpired { name });
+    }
+
+    let previous_owner = std::mem::replace(&mut record.owner, new_owner.clone());
+    name_records().save(deps.storage, key, &record)?;
+    // records and the primary name were chosen by the previous owner
+    clear_owner_records(deps.storage, key)?;
+    clear_primary_name(deps.storage, &previous_owner, &name)?;
+    let result_txt = format!(
+        "execute_transfer, name: {}, to: {}, chain_id: {}",
+        name, new_owner.address, new_owner.chain_id
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = Response::new().set_data(result);
+    Ok(response)
+}
+
+// the new owner stays on the caller's chain unless told otherwise,
+// a remote owner has to be on a registered chain so its address can be validated
+fn new_owner(
+    deps: Deps,
+    env: &Env,
+    caller: &Owner,
+    to: String,
+    to_chain_id: Option<String>,
+) -> Result<Owner, ContractError> {
+    let chain_id = to_chain_id.unwrap_or_else(|| caller.chain_id.clone());
+    let address = validate_chain_address(deps, env, &chain_id, &to)?;
+    Ok(Owner { chain_id, address })
+}
+
+// validate_chain_address checks address against the address family of chain_id:
+// the local chain, or a registered chain
+fn validate_chain_address(
+    deps: Deps,
+    env: &Env,
+    chain_id: &str,
+    address: &str,
+) -> Result<String, ContractError> {
+    if chain_id == env.block.chain_id {
+        return Ok(deps.api.addr_validate(address)?.to_string());
+    }
+    let chain = CHAINS
+        .may_load(deps.storage, chain_id)?
+        .ok_or_else(|| ContractError::ChainNotRegistered {
+            dest_chain_id: chain_id.to_string(),
+        })?;
+    validate_address(&chain.chain_type, address)
+}
+
+// anyone can pay to extend a lease, the extension starts at the current expiry
+// so renewing during the grace period doesn't give away the expired time.
+// subdomains can't outlive their parent, renewing one catches up with the parent for free
+pub fn execute_renew(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    years: u32,
+) -> Result<Response, ContractError> {
+    // a subdomain follows the lease of its parent, years don't apply and it can't be paid for
+    if let Some(parent) = parent_name(&name) {
+        nonpayable(&info)?;
+        let parent = name_records()
+            .may_load(deps.storage, parent.as_bytes())?
+            .ok_or_else(|| ContractError::NameNotExists {
+                name: parent.to_string(),
+            })?;
+        let record = name_records().update(deps.storage, name.as_bytes(), |record| match record {
+            Some(mut record) if !record.is_released(&env) => {
+                record.expires = parent.expires;
+                Ok(record)
             }
+            Some(_) => Err(ContractError::NameExpired { name: name.clone() }),
+            None => Err(ContractError::NameNotExists { name: name.clone() }),
+        })?;
+        let result_txt = format!(
+            "execute_renew, name: {}, expires: {}",
+            name,
+            record.expires.seconds()
+        );
+        let result = abi_encode_string(&result_txt);
+        save_debug_result(deps.storage, &result)?;
+        return Ok(Response::new().set_data(result));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    assert_sent_sufficient_coin(&info.funds, lease_price(config.purchase_price, years)?)?;
 
-            record.owner = new_owner.clone();
+    let key = name.as_bytes();
+    let record = name_records().update(deps.storage, key, |record| match record {
+        Some(mut record) if !record.is_released(&env) => {
+            record.expires = record.expires.plus_seconds(SECONDS_PER_YEAR * years as u64);
             Ok(record)
-        } else {
-            Err(ContractError::NameNotExists { name: name.clone() })
         }
+        Some(_) => Err(ContractError::NameExpired { name: name.clone() }),
+        None => Err(ContractError::NameNotExists { name: name.clone() }),
+    })?;
+    let result_txt = format!(
+        "execute_renew, name: {}, expires: {}",
+        name,
+        record.expires.seconds()
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = Response::new().set_data(result);
+    Ok(response)
+}
+
+pub fn execute_set_target(
+    deps: DepsMut,
+    env: Env,
+    caller: Owner,
+    name: String,
+    chain_id: String,
+    address: String,
+) -> Result<Response, ContractError> {
+    let key = name.as_bytes();
+    assert_name_owner(deps.storage, &env, &caller, &name)?;
+
+    let address = validate_chain_address(deps.as_ref(), &env, &chain_id, &address)?;
+    NAME_TARGETS.save(deps.storage, (key, &chain_id), &address)?;
+
+    let result_txt = format!(
+        "execute_set_target, name: {}, chain_id: {}, address: {}",
+        name, chain_id, address
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = Response::new().set_data(result);
+    Ok(response)
+}
+
+pub fn execute_remove_target(
+    deps: DepsMut,
+    env: Env,
+    caller: Owner,
+    name: String,
+    chain_id: String,
+) -> Result<Response, ContractError> {
+    assert_name_owner(deps.storage, &env, &caller, &name)?;
+    NAME_TARGETS.remove(deps.storage, (name.as_bytes(), &chain_id));
+
+    let result_txt = format!("execute_remove_target, name: {}, chain_id: {}", name, chain_id);
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = Response::new().set_data(result);
+    Ok(response)
+}
+
+pub fn execute_set_primary_name(
+    deps: DepsMut,
+    env: Env,
+    caller: Owner,
+    name: String,
+) -> Result<Response, ContractError> {
+    assert_name_owner(deps.storage, &env, &caller, &name)?;
+    PRIMARY_NAMES.save(
+        deps.storage,
+        (caller.chain_id.as_str(), caller.address.as_str()),
+        &name,
+    )?;
+
+    let result_txt = format!(
+        "execute_set_primary_name, name: {}, owner: {}, chain_id: {}",
+        name, caller.address, caller.chain_id
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = Response::new().set_data(result);
+    Ok(response)
+}
+
+// the subdomain lease ends with the parent's
+pub fn execute_create_subdomain(
+    deps: DepsMut,
+    env: Env,
+    caller: Owner,
+    parent: String,
+    label: String,
+    owner: String,
+    owner_chain_id: Option<String>,
+) -> Result<Response, ContractError> {
+    validate_label(&label)?;
+    let name = format!("{}.{}", label, parent);
+    validate_name(&name)?;
+    let parent_record = assert_name_owner(deps.storage, &env, &caller, &parent)
+        .map_err(|err| match err {
+            ContractError::Unauthorized {} => ContractError::NotParentOwner {
+                parent: parent.clone(),
+            },
+            err => err,
+        })?;
+
+    let key = name.as_bytes();
+    let previous = name_records().may_load(deps.storage, key)?;
+    if let Some(current) = &previous {
+        if !current.is_released(&env) {
+            return Err(ContractError::NameTaken { name });
+        }
+    }
+    let owner = new_owner(deps.as_ref(), &env, &caller, owner, owner_chain_id)?;
+    let record = NameRecord {
+        owner: owner.clone(),
+        expires: parent_record.expires,
+    };
+    name_records().save(deps.storage, key, &record)?;
+    SUBDOMAINS.save(deps.storage, (parent.as_str(), label.as_str()), &Empty {})?;
+    clear_owner_records(deps.storage, key)?;
+    if let Some(previous) = previous {
+        clear_primary_name(deps.storage, &previous.owner, &name)?;
+        remove_subdomains(deps.storage, &name)?;
+    }
+
+    let result_txt = format!(
+        "execute_create_subdomain, name: {}, owner: {}, chain_id: {}",
+        name, owner.address, owner.chain_id
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = Response::new().set_data(result);
+    Ok(response)
+}
+
+// the owner of the parent can take a subdomain back, it is removed with its records
+pub fn execute_reclaim_subdomain(
+    deps: DepsMut,
+    env: Env,
+    caller: Owner,
+    name: String,
+) -> Result<Response, ContractError> {
+    let parent = parent_name(&name)
+        .ok_or_else(|| ContractError::NameNotExists { name: name.clone() })?
+        .to_string();
+    assert_name_owner(deps.storage, &env, &caller, &parent).map_err(|err| match err {
+        ContractError::Unauthorized {} => ContractError::NotParentOwner {
+            parent: parent.clone(),
+        },
+        err => err,
     })?;
-    let result_txt = format!("execute_register, name: {}, to: {}", name, to);
+    if !name_records().has(deps.storage, name.as_bytes()) {
+        return Err(ContractError::NameNotExists { name });
+    }
+    remove_subdomain(deps.storage, &name)?;
+
+    let result_txt = format!("execute_reclaim_subdomain, name: {}", name);
     let result = abi_encode_string(&result_txt);
-    RESULT.save(deps.storage, &result)?;
+    save_debug_result(deps.storage, &result)?;
     let response = Response::new().set_data(result);
     Ok(response)
 }
 
+// only the owner of a name that hasn't expired may change it
+fn assert_name_owner(
+    storage: &dyn Storage,
+    env: &Env,
+    caller: &Owner,
+    name: &str,
+) -> Result<NameRecord, ContractError> {
+    let record = name_records()
+        .may_load(storage, name.as_bytes())?
+        .ok_or_else(|| ContractError::NameNotExists {
+            name: name.to_string(),
+        })?;
+    if *caller != record.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if record.is_expired(env) {
+        return Err(ContractError::NameExpired {
+            name: name.to_string(),
+        });
+    }
+    Ok(record)
+}
+
+// reverse resolution also checks ownership on read, so names that expire
+// without changing hands don't need to be cleared here
+fn clear_primary_name(
+    storage: &mut dyn Storage,
+    owner: &Owner,
+    name: &str,
+) -> Result<(), ContractError> {
+    let key = (owner.chain_id.as_str(), owner.address.as_str());
+    if PRIMARY_NAMES.may_load(storage, key)?.as_deref() == Some(name) {
+        PRIMARY_NAMES.remove(storage, key);
+    }
+    Ok(())
+}
+
+// subdomains were handed out by the previous owner of parent, they go when it changes
+// hands after its release
+fn remove_subdomains(storage: &mut dyn Storage, parent: &str) -> Result<(), ContractError> {
+    let labels = SUBDOMAINS
+        .prefix(parent)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for label in labels {
+        remove_subdomain(storage, &format!("{}.{}", label, parent))?;
+    }
+    Ok(())
+}
+
+// remove_subdomain removes name with its records, and the subdomains below it
+fn remove_subdomain(storage: &mut dyn Storage, name: &str) -> Result<(), ContractError> {
+    let key = name.as_bytes();
+    if let Some(record) = name_records().may_load(storage, key)? {
+        clear_primary_name(storage, &record.owner, name)?;
+        name_records().remove(storage, key)?;
+    }
+    clear_owner_records(storage, key)?;
+    if let Some((label, parent)) = name.split_once('.') {
+        SUBDOMAINS.remove(storage, (parent, label));
+    }
+    remove_subdomains(storage, name)
+}
+
+// targets and approvals belong to the owner who set them
+fn clear_owner_records(storage: &mut dyn Storage, key: &[u8]) -> Result<(), ContractError> {
+    let chain_ids = NAME_TARGETS
+        .prefix(key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for chain_id in chain_ids {
+        NAME_TARGETS.remove(storage, (key, &chain_id));
+    }
+    let spenders = APPROVALS
+        .prefix(key)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for spender in spenders {
+        APPROVALS.remove(storage, (key, &spender));
+    }
+    Ok(())
+}
+
+fn is_approved(
+    storage: &dyn Storage,
+    env: &Env,
+    key: &[u8],
+    caller: &Owner,
+) -> Result<bool, ContractError> {
+    if !caller.is_local(env) {
+        return Ok(false);
+    }
+    let approval = APPROVALS.may_load(storage, (key, caller.address.as_str()))?;
+    Ok(approval.is_some_and(|expires| !expires.is_expired(&env.block)))
+}
+
+// cw721 transfer, token_id is the name and the recipient is local
+pub fn execute_transfer_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let caller = Owner::local(&env, &info.sender);
+    let chain_id = env.block.chain_id.clone();
+    execute_transfer(deps, env, info, caller, token_id, recipient, Some(chain_id))
+}
+
+// cw721 send, transfers the name to a contract and calls its ReceiveNft hook
+pub fn execute_send_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    contract: String,
+    token_id: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    let receive_msg = ReceiverExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+        sender: info.sender.to_string(),
+        token_id: token_id.clone(),
+        msg,
+    });
+    let receive_msg = wasm_execute(contract.to_string(), &receive_msg, vec![])?;
+    let response = execute_transfer_nft(deps, env, info, contract.to_string(), token_id)?;
+    Ok(response.add_message(receive_msg))
+}
+
+// owner only, the spender can transfer or send the name until the approval expires
+pub fn execute_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let caller = Owner::local(&env, &info.sender);
+    assert_name_owner(deps.storage, &env, &caller, &token_id)?;
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::ApprovalExpired {});
+    }
+    let spender = deps.api.addr_validate(&spender)?;
+    APPROVALS.save(deps.storage, (token_id.as_bytes(), spender.as_str()), &expires)?;
+
+    let response = Response::new().add_event(
+        Event::new("Approve")
+            .add_attribute("token_id", token_id)
+            .add_attribute("spender", spender)
+            .add_attribute("expires", expires.to_string()),
+    );
+    Ok(response)
+}
+
+pub fn execute_revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let caller = Owner::local(&env, &info.sender);
+    assert_name_owner(deps.storage, &env, &caller, &token_id)?;
+    APPROVALS.remove(deps.storage, (token_id.as_bytes(), spender.as_str()));
+
+    let response = Response::new().add_event(
+        Event::new("Revoke")
+            .add_attribute("token_id", token_id)
+            .add_attribute("spender", spender),
+    );
+    Ok(response)
+}
+
+// anyone can auction a name nobody holds, the auction can't undercut the purchase price
+// the starter escrows the opening bid, so an auction can't hold a name for free
+pub fn execute_start_auction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    min_bid: Coin,
+) -> Result<Response, ContractError> {
+    validate_name(&name)?;
+    if let Some(parent) = parent_name(&name) {
+        return Err(ContractError::NotParentOwner {
+            parent: parent.to_string(),
+        });
+    }
+    validate_price(&Some(min_bid.clone()))?;
+
+    let key = name.as_bytes();
+    if let Some(record) = name_records().may_load(deps.storage, key)? {
+        if !record.is_released(&env) {
+            return Err(ContractError::NameTaken { name });
+        }
+    }
+    if AUCTIONS.has(deps.storage, key) {
+        return Err(ContractError::AuctionInProgress { name });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(price) = config.purchase_price {
+        if price.denom != min_bid.denom || min_bid.amount < price.amount {
+            return Err(ContractError::BidTooLow {
+                minimum: price.to_string(),
+            });
+        }
+    }
+
+    let amount = must_pay(&info, &min_bid.denom)?;
+    if amount < min_bid.amount {
+        return Err(ContractError::BidTooLow {
+            minimum: min_bid.to_string(),
+        });
+    }
+    let bid = Bid {
+        bidder: Owner::local(&env, &info.sender),
+        amount: Coin::new(amount.u128(), min_bid.denom.clone()),
+    };
+
+    let auction = Auction {
+        min_bid,
+        end: env.block.time.plus_seconds(AUCTION_DURATION_SECONDS),
+        highest_bid: Some(bid.clone()),
+    };
+    AUCTIONS.save(deps.storage, key, &auction)?;
+
+    let response = Response::new().add_event(
+        Event::new("StartAuction")
+            .add_attribute("name", name)
+            .add_attribute("min_bid", auction.min_bid.to_string())
+            .add_attribute("bidder", bid.bidder.address)
+            .add_attribute("amount", bid.amount.to_string())
+            .add_attribute("end", auction.end.seconds().to_string()),
+    );
+    Ok(response)
+}
+
+// bidder is the local sender, or the remote request sender for packets coming through IReceive.
+// the bid is escrowed by the contract and the outbid bidder is refunded
+pub fn execute_bid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bidder: Owner,
+    name: String,
+) -> Result<Response, ContractError> {
+    let key = name.as_bytes();
+    let mut auction = AUCTIONS
+        .may_load(deps.storage, key)?
+        .ok_or_else(|| ContractError::AuctionNotFound { name: name.clone() })?;
+    if env.block.time >= auction.end {
+        return Err(ContractError::AuctionEnded { name });
+    }
+
+    let denom = auction.min_bid.denom.clone();
+    let amount = must_pay(&info, &denom)?;
+    let minimum = match &auction.highest_bid {
+        Some(bid) => bid.amount.amount + Uint128::one(),
+        None => auction.min_bid.amount,
+    };
+    if amount < minimum {
+        return Err(ContractError::BidTooLow {
+            minimum: Coin::new(minimum.u128(), denom).to_string(),
+        });
+    }
+
+    let mut response = Response::new();
+    if let Some(outbid) = auction.highest_bid.take() {
+        response = refund(deps.storage, &env, outbid.bidder, outbid.amount, response)?;
+    }
+    let bid = Bid {
+        bidder: bidder.clone(),
+        amount: Coin::new(amount.u128(), denom),
+    };
+    auction.highest_bid = Some(bid.clone());
+    AUCTIONS.save(deps.storage, key, &auction)?;
+
+    let result_txt = format!(
+        "execute_bid, name: {}, bidder: {}, chain_id: {}, amount: {}",
+        name, bidder.address, bidder.chain_id, bid.amount
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let response = response
+        .add_event(
+            Event::new("Bid")
+                .add_attribute("name", name)
+                .add_attribute("bidder", bidder.address)
+                .add_attribute("chain_id", bidder.chain_id)
+                .add_attribute("amount", bid.amount.to_string()),
+        )
+        .set_data(result);
+    Ok(response)
+}
+
+// local bidders are paid back right away, remote ones get credits to claim
+fn refund(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: Owner,
+    amount: Coin,
+    response: Response,
+) -> Result<Response, ContractError> {
+    if owner.is_local(env) {
+        let refund_msg = BankMsg::Send {
+            to_address: owner.address,
+            amount: vec![amount],
+        };
+        return Ok(response.add_message(refund_msg));
+    }
+    add_credits(storage, &owner, &[amount])?;
+    Ok(response)
+}
+
+pub fn add_credits(
+    storage: &mut dyn Storage,
+    owner: &Owner,
+    funds: &[Coin],
+) -> Result<(), ContractError> {
+    let key = (owner.chain_id.as_str(), owner.address.as_str());
+    let mut credits = CREDITS.may_load(storage, key)?.unwrap_or_default();
+    for coin in funds {
+        match credits.iter_mut().find(|credit| credit.denom == coin.denom) {
+            Some(credit) => credit.amount += coin.amount,
+            None => credits.push(coin.clone()),
+        }
+    }
+    CREDITS.save(storage, key, &credits)?;
+    Ok(())
+}
+
+// anyone can settle an auction after its deadline, the highest bidder gets a one year lease
+pub fn execute_settle_auction(
+    deps: DepsMut,
+    env: Env,
+    name: String,
+) -> Result<Response, ContractError> {
+    let key = name.as_bytes();
+    let auction = AUCTIONS
+        .may_load(deps.storage, key)?
+        .ok_or_else(|| ContractError::AuctionNotFound { name: name.clone() })?;
+    if env.block.time < auction.end {
+        return Err(ContractError::AuctionNotEnded { name });
+    }
+    AUCTIONS.remove(deps.storage, key);
+
+    let mut event = Event::new("SettleAuction").add_attribute("name", name.as_str());
+    if let Some(bid) = auction.highest_bid {
+        let record = NameRecord {
+            owner: bid.bidder.clone(),
+            expires: env.block.time.plus_seconds(SECONDS_PER_YEAR),
+        };
+        if let Some(previous) = name_records().may_load(deps.storage, key)? {
+            clear_primary_name(deps.storage, &previous.owner, &name)?;
+            remove_subdomains(deps.storage, &name)?;
+        }
+        name_records().save(deps.storage, key, &record)?;
+        clear_owner_records(deps.storage, key)?;
+        event = event
+            .add_attribute("winner", bid.bidder.address)
+            .add_attribute("chain_id", bid.bidder.chain_id)
+            .add_attribute("amount", bid.amount.to_string());
+    }
+    Ok(Response::new().add_event(event))
+}
+
+// caller is the remote request sender, its credits are paid out to a local recipient
+pub fn execute_claim_credits(
+    deps: DepsMut,
+    caller: Owner,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let key = (caller.chain_id.as_str(), caller.address.as_str());
+    let credits = CREDITS
+        .may_load(deps.storage, key)?
+        .filter(|credits| !credits.is_empty())
+        .ok_or(ContractError::NoCredits {})?;
+    CREDITS.remove(deps.storage, key);
+
+    let result_txt = format!(
+        "execute_claim_credits, recipient: {}, amount: {:?}",
+        recipient, credits
+    );
+    let result = abi_encode_string(&result_txt);
+    save_debug_result(deps.storage, &result)?;
+    let claim_msg = BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: credits,
+    };
+    let response = Response::new().add_message(claim_msg).set_data(result);
+    Ok(response)
+}
+
 pub fn execute_i_receive(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    _src_chain_id: String,
-    _request_sender: String,
+    src_chain_id: String,
+    request_sender: String,
+    payload: Binary,
+    request_identifier: Option<u64>,
+) -> Result<Response, ContractError> {
+    // only the router gateway may deliver inbound packets
+    let config = CONFIG.load(deps.storage)?;
+    assert_gateway(&config, &info.sender)?;
+    // and only on behalf of an allowlisted remote handler
+    assert_trusted_sender(deps.storage, &src_chain_id, &request_sender)?;
+
+    // every packet is executed at most once
+    let packet_key = inbound_packet_key(&src_chain_id, &request_sender, &payload, request_identifier);
+    if PROCESSED_PACKETS.has(deps.storage, &packet_key) {
+        return Err(ContractError::PacketAlreadyProcessed {});
+    }
+    PROCESSED_PACKETS.save(deps.storage, &packet_key, &env.block.height)?;
+
+    // names touched by a remote packet belong to the remote request sender, not the gateway
+    let caller = Owner {
+        chain_id: src_chain_id,
+        address: normalize_sender(&request_sender),
+    };
+
+    // handler errors don't revert the transaction, they are reported to the source chain
+    // in the ack payload instead. the handler runs as a submessage to this contract,
+    // so the chain reverts its writes when it fails, and the reply builds the ack
+    let pending = PendingIReceive {
+        caller: caller.clone(),
+        funds: info.funds.clone(),
+        payload: payload.clone(),
+    };
+    PENDING_I_RECEIVE.save(deps.storage, &pending)?;
+    let handle_msg = ExecuteMsg::HandleIReceive { caller, payload };
+    let handle_msg = wasm_execute(env.contract.address, &handle_msg, info.funds)?;
+    let response = Response::new().add_submessage(SubMsg::reply_always(handle_msg, I_RECEIVE_ID));
+    Ok(response)
+}
+
+pub fn execute_handle_i_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    caller: Owner,
+    payload: Binary,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    dispatch_i_receive(deps, env, info, caller, payload)
+}
+
+// the reply of a failed handler only carries a redacted error string. replaying the
+// handler on throwaway storage recovers the error, so the ack can carry its code
+pub fn replay_i_receive(deps: Deps, env: &Env, pending: &PendingIReceive) -> Option<ContractError> {
+    let mut storage = DryRunStorage::new(deps.storage);
+    let deps = DepsMut {
+        storage: &mut storage,
+        api: deps.api,
+        querier: deps.querier,
+    };
+    let info = MessageInfo {
+        sender: env.contract.address.clone(),
+        funds: pending.funds.clone(),
+    };
+    dispatch_i_receive(deps, env.clone(), info, pending.caller.clone(), pending.payload.clone()).err()
+}
+
+// handlers take the caller as an Owner: the local sender for messages sent to this chain,
+// or the remote request sender for packets dispatched here
+fn dispatch_i_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    caller: Owner,
     payload: Binary,
 ) -> Result<Response, ContractError> {
     // abi decode payload, which was encoded by request sender
     let decoded = abi_decode_to_binary(&payload)?;
+    let msg: CustomExecuteMsg = from_binary(&decoded)?;
 
     // save request packet for debug
-    REQUEST.save(deps.storage, &decoded)?;
+    save_debug_request(deps.storage, &decoded)?;
 
-    let msg: CustomExecuteMsg = from_binary(&decoded)?;
     match msg {
-        CustomExecuteMsg::Register { name } => execute_register(deps, env, info, name),
-        CustomExecuteMsg::Transfer { name, to } => execute_transfer(deps, env, info, name, to),
+        CustomExecuteMsg::Register { name, years } => {
+            execute_register(deps, env, info, caller, name, years.unwrap_or(1))
+        }
+        CustomExecuteMsg::Renew { name, years } => execute_renew(deps, env, info, name, years),
+        CustomExecuteMsg::SetTarget {
+            name,
+            chain_id,
+            address,
+        } => execute_set_target(deps, env, caller, name, chain_id, address),
+        CustomExecuteMsg::RemoveTarget { name, chain_id } => {
+            execute_remove_target(deps, env, caller, name, chain_id)
+        }
+        CustomExecuteMsg::CreateSubdomain {
+            parent,
+            label,
+            owner,
+            owner_chain_id,
+        } => execute_create_subdomain(deps, env, caller, parent, label, owner, owner_chain_id),
+        CustomExecuteMsg::ReclaimSubdomain { name } => {
+            execute_reclaim_subdomain(deps, env, caller, name)
+        }
+        CustomExecuteMsg::SetPrimaryName { name } => {
+            execute_set_primary_name(deps, env, caller, name)
+        }
+        CustomExecuteMsg::Bid { name } => execute_bid(deps, env, info, caller, name),
+        CustomExecuteMsg::ClaimCredits { recipient } => {
+            execute_claim_credits(deps, caller, recipient)
+        }
+        CustomExecuteMsg::Transfer {
+            name,
+            to,
+            to_chain_id,
+        } => execute_transfer(deps, env, info, caller, name, to, to_chain_id),
     }
 }
 
 pub fn execute_i_ack(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     request_identifier: u64,
     exec_status: bool,
     exec_data: Binary,
 ) -> Result<Response, ContractError> {
+    // only the router gateway may deliver acknowledgements
+    let config = CONFIG.load(deps.storage)?;
+    assert_gateway(&config, &info.sender)?;
+
     // abi decode payload, which was encoded by request sender
     let decoded = abi_decode_to_binary(&exec_data)?;
 
     // save ack packet in store for debug
-    REQUEST.save(deps.storage, &decoded)?;
+    save_debug_request(deps.storage, &decoded)?;
+
+    // move the tracked outbound request to its final state
+    let requests = outbound_requests();
+    if let Some(mut request) = requests.may_load(deps.storage, request_identifier)? {
+        if request.status != RequestStatus::Pending {
+            return Err(ContractError::RequestAlreadyAcked { request_identifier });
+        }
+        request.status = if exec_status {
+            RequestStatus::AckSuccess
+        } else {
+            RequestStatus::AckFailure
+        };
+        requests.save(deps.storage, request_identifier, &request)?;
+
+        // a request that failed on the destination chain can be retried
+        if !exec_status {
+            enqueue_retry(
+                deps.storage,
+                request,
+                RetrySource::AckFailure { request_identifier },
+            )?;
+        }
+    }
+
+    // keep the outcome so callers can poll it later
+    let ack_result = AckResult {
+        exec_status,
+        exec_data: decoded.clone(),
+    };
+    ACK_RESULTS.save(deps.storage, request_identifier, &ack_result)?;
 
     let result_txt = format!("Ack from handler contract:\naddress: {}\nrequest_identifier: {}\nexec_status:{}\nexec_data:{:?}", 
-    env.contract.address.to_string(), request_identifier, exec_status, decoded);
+    env.contract.address, request_identifier, exec_status, decoded);
 
     // abi encode result_txt
     let result = abi_encode_string(&result_txt);
 
     // save result in store for debug
-    RESULT.save(deps.storage, &result)?;
+    save_debug_result(deps.storage, &result)?;
 
     Ok(Response::new().set_data(result))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_i_send(
-    _deps: DepsMut,
-    _env: Env,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     version: u64,
     route_amount: u64,
     route_recipient: String,
     dest_chain_id: String,
     request_metadata: Binary,
-    gateway_address: String,
     handler_address: String,
     payload: Binary,
 ) -> Result<Response, ContractError> {
-    // request_packet = abi encode(handler_address, payload)
-    let request_packet = get_request_packet(&handler_address, &payload);
+    assert_admin(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    let request = new_outbound_request(
+        &env,
+        info.sender,
+        version,
+        route_amount,
+        route_recipient,
+        dest_chain_id,
+        handler_address,
+        request_metadata,
+        &payload,
+    );
+    let event = i_send_event(&request, &payload);
+    let submsg = i_send_submsg(deps.storage, &config.gateway_address, request)?;
 
-    // create ISend msg of gateway contract
-    let i_send_msg = GatewayMsg::ISend {
+    Ok(Response::new().add_event(event).add_submessage(submsg))
+}
+
+// every entry is sent through the config gateway as its own submessage.
+// unless allow_partial_failure is set, a failed entry reverts the whole batch
+pub fn execute_i_send_batch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    requests: Vec<ISendRequest>,
+    allow_partial_failure: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    if requests.is_empty() || requests.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::InvalidBatchSize {
+            size: requests.len() as u64,
+            max: MAX_BATCH_SIZE as u64,
+        });
+    }
+    let config = CONFIG.load(deps.storage)?;
+
+    // replies fill in the entries in order, the last one reports the whole batch
+    let batch = PendingBatch {
+        remaining: requests.len() as u32,
+        allow_partial_failure,
+        entries: vec![],
+    };
+    PENDING_BATCH.save(deps.storage, &batch)?;
+
+    let mut response = Response::new();
+    for (index, entry) in requests.into_iter().enumerate() {
+        let request_metadata = resolve_request_metadata(entry.request_metadata, entry.metadata)?;
+        let request = new_outbound_request(
+            &env,
+            info.sender.clone(),
+            entry.version,
+            entry.route_amount,
+            entry.route_recipient,
+            entry.dest_chain_id,
+            entry.handler_address,
+            request_metadata,
+            &entry.payload,
+        );
+        let event = i_send_event(&request, &entry.payload)
+            .add_attribute("batch_index", index.to_string());
+        let submsg = i_send_submsg(deps.storage, &config.gateway_address, request)?;
+        response = response.add_event(event).add_submessage(submsg);
+    }
+
+    Ok(response)
+}
+
+// ISendTo fills in handler address and metadata from the chain registry,
+// anything passed explicitly overrides the registry defaults
+#[allow(clippy::too_many_arguments)]
+pub fn execute_i_send_to(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dest_chain_id: String,
+    payload: Binary,
+    handler_address: Option<String>,
+    metadata: Option<RequestMetadata>,
+    route_amount: Option<u64>,
+    route_recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    let chain = CHAINS
+        .may_load(deps.storage, &dest_chain_id)?
+        .ok_or_else(|| ContractError::ChainNotRegistered {
+            dest_chain_id: dest_chain_id.clone(),
+        })?;
+
+    let handler_address = match handler_address {
+        Some(handler_address) => validate_handler_address(&chain.chain_type, &handler_address)?,
+        None => chain.handler_address,
+    };
+    let request_metadata = match metadata {
+        Some(metadata) => encode_request_metadata(&metadata)?,
+        None => chain.request_metadata,
+    };
+
+    let request = new_outbound_request(
+        &env,
+        info.sender,
+        DEFAULT_ISEND_VERSION,
+        route_amount.unwrap_or_default(),
+        route_recipient.unwrap_or_default(),
+        dest_chain_id,
+        handler_address,
+        request_metadata,
+        &payload,
+    );
+    let event = i_send_event(&request, &payload);
+    let submsg = i_send_submsg(deps.storage, &config.gateway_address, request)?;
+
+    Ok(Response::new().add_event(event).add_submessage(submsg))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn new_outbound_request(
+    env: &Env,
+    caller: Addr,
+    version: u64,
+    route_amount: u64,
+    route_recipient: String,
+    dest_chain_id: String,
+    handler_address: String,
+    request_metadata: Binary,
+    payload: &Binary,
+) -> OutboundRequest {
+    // request_packet = abi encode(handler_address, payload)
+    let request_packet = get_request_packet(&handler_address, payload);
+    OutboundRequest {
         version,
         route_amount,
         route_recipient,
-        dest_chain_id: dest_chain_id.clone(),
+        dest_chain_id,
+        handler_address,
+        request_packet,
+        payload_hash: hex::encode(sha256(payload.as_slice())),
         request_metadata,
-        request_packet: request_packet.clone(),
+        caller,
+        height: env.block.height,
+        time: env.block.time,
+        status: RequestStatus::Pending,
+        retry_of: None,
+        original_request: None,
+        attempt: 0,
+    }
+}
+
+// raw metadata doesn't have to follow the structured layout (e.g. templates), it is
+// emitted as hex when it can't be decoded
+fn i_send_event(request: &OutboundRequest, payload: &Binary) -> Event {
+    let event = Event::new("ISend")
+        .add_attribute("dest_chain_id", request.dest_chain_id.clone())
+        .add_attribute("handler_address", request.handler_address.clone())
+        .add_attribute("payload", payload.to_base64())
+        .add_attribute("request_packet", request.request_packet.to_base64());
+    let metadata = match decode_request_metadata(&request.request_metadata) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            return event.add_attribute(
+                "request_metadata",
+                hex::encode(request.request_metadata.as_slice()),
+            )
+        }
     };
+    event
+        .add_attribute("gas_limit", metadata.gas_limit.to_string())
+        .add_attribute("gas_price", metadata.gas_price.to_string())
+        .add_attribute("ack_gas_limit", metadata.ack_gas_limit.to_string())
+        .add_attribute("ack_gas_price", metadata.ack_gas_price.to_string())
+        .add_attribute("relayer_fees", metadata.relayer_fees)
+        .add_attribute("ack_type", metadata.ack_type.to_string())
+        .add_attribute("is_read_call", metadata.is_read_call.to_string())
+        .add_attribute("asm_address", metadata.asm_address)
+}
 
-    let gateway_send_msg = wasm_execute(gateway_address, &i_send_msg, vec![])?;
+// i_send_submsg builds the gateway ISend submessage for request.
+// the gateway hands out the request_identifier in the ISend reply, so the request is
+// queued until then. submessages and their replies run in order, so replies pop the
+// queue in the order the submessages were added
+fn i_send_submsg(
+    storage: &mut dyn Storage,
+    gateway_address: &Addr,
+    request: OutboundRequest,
+) -> Result<SubMsg, ContractError> {
+    // create ISend msg of gateway contract
+    let i_send_msg = GatewayMsg::ISend {
+        version: request.version,
+        route_amount: request.route_amount,
+        route_recipient: request.route_recipient.clone(),
+        dest_chain_id: request.dest_chain_id.clone(),
+        request_metadata: request.request_metadata.clone(),
+        request_packet: request.request_packet.clone(),
+    };
+    PENDING_SENDS.push_back(storage, &request)?;
+
+    let gateway_send_msg = wasm_execute(gateway_address.to_string(), &i_send_msg, vec![])?;
     let submsg = SubMsg {
         id: ISEND_ID,
         gas_limit: None,
         reply_on: ReplyOn::Always,
         msg: gateway_send_msg.into(),
     };
+    Ok(submsg)
+}
+
+/// enqueue_retry puts a failed outbound request in the retry queue.
+/// a request that is itself a retry goes back under its original retry id
+pub fn enqueue_retry(
+    storage: &mut dyn Storage,
+    request: OutboundRequest,
+    source: RetrySource,
+) -> Result<u64, ContractError> {
+    let id = match request.retry_of {
+        Some(id) => id,
+        None => {
+            let id = RETRY_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+            RETRY_SEQ.save(storage, &id)?;
+            id
+        }
+    };
+    RETRY_QUEUE.save(storage, id, &RetryEntry { request, source })?;
+    Ok(id)
+}
 
-    let response = Response::new()
-        .add_event(Event::new("ISend")
-                .add_attribute("dest_chain_id", dest_chain_id)
-                .add_attribute("handler_address", handler_address)
-                .add_attribute("payload", payload.to_base64())
-                .add_attribute("request_packet", request_packet.to_base64()),
-        )
-        .add_submessage(submsg);
+pub fn execute_retry(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ids: Vec<u64>,
+    metadata: Option<RequestMetadata>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let is_admin = assert_admin(deps.storage, &info.sender).is_ok();
+    // bumped gas/fee metadata applies to every retried request
+    let request_metadata = metadata.as_ref().map(encode_request_metadata).transpose()?;
+
+    let mut response = Response::new();
+    for id in ids {
+        let entry = RETRY_QUEUE.load(deps.storage, id)?;
+        if !is_admin && entry.request.caller != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let attempt = entry.request.attempt + 1;
+        if attempt > config.max_retry_attempts {
+            return Err(ContractError::RetryLimitReached {
+                id,
+                max_attempts: config.max_retry_attempts,
+            });
+        }
+        RETRY_QUEUE.remove(deps.storage, id);
+
+        // the queue entry is gone once retried, the request keeps what it retries
+        let original_request = entry.request.original_request.clone().or(Some(entry.source));
+        let request = OutboundRequest {
+            request_metadata: request_metadata
+                .clone()
+                .unwrap_or(entry.request.request_metadata),
+            height: env.block.height,
+            time: env.block.time,
+            status: RequestStatus::Pending,
+            retry_of: Some(id),
+            original_request,
+            attempt,
+            ..entry.request
+        };
+        let submsg = i_send_submsg(deps.storage, &config.gateway_address, request)?;
+        response = response
+            .add_event(
+                Event::new("Retry")
+                    .add_attribute("retry_id", id.to_string())
+                    .add_attribute("attempt", attempt.to_string()),
+            )
+            .add_submessage(submsg);
+    }
 
     Ok(response)
 }
 
+pub fn execute_set_max_retry_attempts(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_retry_attempts: u32,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.max_retry_attempts = max_retry_attempts;
+    CONFIG.save(deps.storage, &config)?;
+
+    let response = Response::new().add_event(
+        Event::new("SetMaxRetryAttempts")
+            .add_attribute("max_retry_attempts", max_retry_attempts.to_string()),
+    );
+    Ok(response)
+}
+
 pub fn set_dapp_metadata(
-    _deps: DepsMut,
+    deps: DepsMut,
+    info: MessageInfo,
     fee_payer_address: String,
-    gateway_address: String,
 ) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    // the fee payer is recorded once the gateway accepts it
+    PENDING_FEE_PAYER.save(deps.storage, &fee_payer_address)?;
+
     // create SetDappMetadata msg of gateway contract
     let set_dapp_metadata_msg = GatewayMsg::SetDappMetadata {
         fee_payer_address: fee_payer_address.clone(),
     };
 
-    let gateway_send_msg = wasm_execute(gateway_address, &set_dapp_metadata_msg, vec![])?;
+    let gateway_send_msg =
+        wasm_execute(config.gateway_address.to_string(), &set_dapp_metadata_msg, vec![])?;
     let submsg = SubMsg {
-        id: ISEND_ID,
+        id: SET_DAPP_METADATA_ID,
         gas_limit: None,
         reply_on: ReplyOn::Always,
         msg: gateway_send_msg.into(),
@@ -196,3 +1286,200 @@ pub fn set_dapp_metadata(
 
     Ok(response)
 }
+
+pub fn execute_propose_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    admin: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let admin = deps.api.addr_validate(&admin)?;
+    PENDING_ADMIN.save(deps.storage, &admin)?;
+
+    let response = Response::new().add_event(
+        Event::new("ProposeAdmin")
+            .add_attribute("admin", info.sender)
+            .add_attribute("pending_admin", admin),
+    );
+    Ok(response)
+}
+
+pub fn execute_accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    match PENDING_ADMIN.may_load(deps.storage)? {
+        Some(pending_admin) if pending_admin == info.sender => {}
+        _ => return Err(ContractError::NotPendingAdmin {}),
+    }
+    let old_admin = ADMIN.may_load(deps.storage)?;
+    ADMIN.save(deps.storage, &info.sender)?;
+    PENDING_ADMIN.remove(deps.storage);
+
+    let response = Response::new().add_event(
+        Event::new("AcceptAdmin")
+            .add_attribute("old_admin", old_admin.map(String::from).unwrap_or_default())
+            .add_attribute("new_admin", info.sender),
+    );
+    Ok(response)
+}
+
+pub fn execute_renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    ADMIN.remove(deps.storage);
+    PENDING_ADMIN.remove(deps.storage);
+
+    let response =
+        Response::new().add_event(Event::new("RenounceAdmin").add_attribute("admin", info.sender));
+    Ok(response)
+}
+
+// prices are replaced as a whole, None disables a price
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    purchase_price: Option<Coin>,
+    transfer_price: Option<Coin>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+    validate_price(&purchase_price)?;
+    validate_price(&transfer_price)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_purchase_price = config.purchase_price;
+    let old_transfer_price = config.transfer_price;
+    config.purchase_price = purchase_price;
+    config.transfer_price = transfer_price;
+    CONFIG.save(deps.storage, &config)?;
+
+    let response = Response::new().add_event(
+        Event::new("UpdateConfig")
+            .add_attribute("old_purchase_price", price_attribute(&old_purchase_price))
+            .add_attribute("new_purchase_price", price_attribute(&config.purchase_price))
+            .add_attribute("old_transfer_price", price_attribute(&old_transfer_price))
+            .add_attribute("new_transfer_price", price_attribute(&config.transfer_price)),
+    );
+    Ok(response)
+}
+
+fn price_attribute(price: &Option<Coin>) -> String {
+    match price {
+        Some(price) => price.to_string(),
+        None => "none".to_string(),
+    }
+}
+
+pub fn execute_update_gateway(
+    deps: DepsMut,
+    info: MessageInfo,
+    gateway_address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let gateway_address = deps.api.addr_validate(&gateway_address)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let old_gateway_address = config.gateway_address;
+    config.gateway_address = gateway_address.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    let response = Response::new().add_event(
+        Event::new("UpdateGateway")
+            .add_attribute("old_gateway_address", old_gateway_address)
+            .add_attribute("new_gateway_address", gateway_address),
+    );
+    Ok(response)
+}
+
+pub fn execute_add_trusted_sender(
+    deps: DepsMut,
+    info: MessageInfo,
+    chain_id: String,
+    sender: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let sender = normalize_sender(&sender);
+    TRUSTED_SENDERS.save(deps.storage, (chain_id.as_str(), sender.as_str()), &Empty {})?;
+
+    let response = Response::new().add_event(
+        Event::new("AddTrustedSender")
+            .add_attribute("chain_id", chain_id)
+            .add_attribute("sender", sender),
+    );
+    Ok(response)
+}
+
+pub fn execute_remove_trusted_sender(
+    deps: DepsMut,
+    info: MessageInfo,
+    chain_id: String,
+    sender: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let sender = normalize_sender(&sender);
+    TRUSTED_SENDERS.remove(deps.storage, (chain_id.as_str(), sender.as_str()));
+
+    let response = Response::new().add_event(
+        Event::new("RemoveTrustedSender")
+            .add_attribute("chain_id", chain_id)
+            .add_attribute("sender", sender),
+    );
+    Ok(response)
+}
+
+pub fn execute_set_chain(
+    deps: DepsMut,
+    info: MessageInfo,
+    dest_chain_id: String,
+    chain_type: ChainType,
+    handler_address: String,
+    metadata: RequestMetadata,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let chain = ChainConfig {
+        handler_address: validate_handler_address(&chain_type, &handler_address)?,
+        request_metadata: encode_request_metadata(&metadata)?,
+        chain_type,
+    };
+    CHAINS.save(deps.storage, &dest_chain_id, &chain)?;
+
+    let response = Response::new().add_event(
+        Event::new("SetChain")
+            .add_attribute("dest_chain_id", dest_chain_id)
+            .add_attribute("chain_type", format!("{:?}", chain.chain_type))
+            .add_attribute("handler_address", chain.handler_address),
+    );
+    Ok(response)
+}
+
+pub fn execute_remove_chain(
+    deps: DepsMut,
+    info: MessageInfo,
+    dest_chain_id: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    CHAINS.remove(deps.storage, &dest_chain_id);
+
+    let response = Response::new()
+        .add_event(Event::new("RemoveChain").add_attribute("dest_chain_id", dest_chain_id));
+    Ok(response)
+}
+
+pub fn execute_set_reply_failure_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    policy: ReplyFailurePolicy,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.storage, &info.sender)?;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.reply_failure_policy = policy.clone();
+    CONFIG.save(deps.storage, &config)?;
+
+    let response = Response::new().add_event(
+        Event::new("SetReplyFailurePolicy").add_attribute("policy", format!("{:?}", policy)),
+    );
+    Ok(response)
+}
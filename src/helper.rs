@@ -1,10 +1,20 @@
-use cosmwasm_std::{Binary, StdError};
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Addr, Binary, Order, Record, StdError, Storage};
 use hex;
 use router_wasm_bindings::ethabi::{ethereum_types::{Address, U256}, ParamType, decode, encode, Token};
-use cosmwasm_std::Coin;
+use cosmwasm_std::{Coin, Uint128};
+use sha2::{Digest, Sha256};
+use bech32::Variant;
 
 use crate::ContractError;
-use crate::consts::{MAX_NAME_LENGTH, MIN_NAME_LENGTH};
+use crate::state::{ChainType, Config, ADMIN, DEBUG_STATE, TRUSTED_SENDERS};
+use crate::consts::{
+    EVM_ADDRESS_LENGTH, MAX_ACK_TYPE, MAX_DENOM_LENGTH, MAX_NAME_LENGTH,
+    MAX_REGISTRATION_YEARS, MIN_DENOM_LENGTH, MIN_NAME_LENGTH,
+    REQUEST_METADATA_FIXED_LENGTH,
+};
+use crate::msg::RequestMetadata;
 
 pub struct TakeLastXBytes(pub usize);
 
@@ -18,7 +28,7 @@ pub enum SolidityDataType<'a> {
 }
 
 /// Pack a single `SolidityDataType` into bytes
-fn pack<'a>(data_type: &'a SolidityDataType) -> Vec<u8> {
+fn pack(data_type: &SolidityDataType) -> Vec<u8> {
     let mut res = Vec::new();
     match data_type {
         SolidityDataType::String(s) => {
@@ -55,7 +65,7 @@ fn pack<'a>(data_type: &'a SolidityDataType) -> Vec<u8> {
             res.extend(local_res);
         }
     };
-    return res;
+    res
 }
 
 pub fn encode_packed(items: &[SolidityDataType]) -> (Vec<u8>, String) {
@@ -69,13 +79,14 @@ pub fn encode_packed(items: &[SolidityDataType]) -> (Vec<u8>, String) {
     (res, hexed)
 }
 
-pub fn get_request_packet(handler_address: &String, payload: &Binary) -> Binary {
-    let handler_token = Token::String(handler_address.clone());
+pub fn get_request_packet(handler_address: &str, payload: &Binary) -> Binary {
+    let handler_token = Token::String(handler_address.to_string());
     let payload_token = Token::Bytes(payload.as_slice().to_vec());
-    let enc = encode(&vec![handler_token, payload_token]);
+    let enc = encode(&[handler_token, payload_token]);
     Binary::from(enc)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn get_request_metadata(
     gas_limit: u64,
     gas_price: u64,
@@ -97,14 +108,131 @@ pub fn get_request_metadata(
         SolidityDataType::String(asm_address.as_str())
     ];
     let (enc, _ )= encode_packed(&input);
-    let enc_bin = Binary::from(enc);
-    enc_bin
+    Binary::from(enc)
+}
+
+/// validate_request_metadata returns an error if the fields can't be understood by the gateway
+pub fn validate_request_metadata(metadata: &RequestMetadata) -> Result<(), ContractError> {
+    if metadata.ack_type > MAX_ACK_TYPE {
+        return Err(ContractError::InvalidRequestMetadata {
+            reason: format!("ack_type {} is greater than {}", metadata.ack_type, MAX_ACK_TYPE),
+        });
+    }
+    if metadata.asm_address.chars().any(|c| !c.is_ascii_graphic()) {
+        return Err(ContractError::InvalidRequestMetadata {
+            reason: "asm_address must be printable ascii".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// encode_request_metadata validates the structured metadata and packs it like get_request_metadata
+pub fn encode_request_metadata(metadata: &RequestMetadata) -> Result<Binary, ContractError> {
+    validate_request_metadata(metadata)?;
+    Ok(get_request_metadata(
+        metadata.gas_limit,
+        metadata.gas_price,
+        metadata.ack_gas_limit,
+        metadata.ack_gas_price,
+        metadata.relayer_fees.u128(),
+        metadata.ack_type,
+        metadata.is_read_call,
+        metadata.asm_address.clone(),
+    ))
+}
+
+/// decode_request_metadata is the inverse of get_request_metadata
+pub fn decode_request_metadata(request_metadata: &Binary) -> Result<RequestMetadata, ContractError> {
+    let bytes = request_metadata.as_slice();
+    if bytes.len() < REQUEST_METADATA_FIXED_LENGTH {
+        return Err(ContractError::InvalidRequestMetadata {
+            reason: format!(
+                "length {} is shorter than {}",
+                bytes.len(),
+                REQUEST_METADATA_FIXED_LENGTH
+            ),
+        });
+    }
+
+    let read_u64 = |offset: usize| {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&bytes[offset..offset + 8]);
+        u64::from_be_bytes(buf)
+    };
+    let mut relayer_fees = [0u8; 16];
+    relayer_fees.copy_from_slice(&bytes[32..48]);
+
+    let is_read_call = match bytes[49] {
+        0 => false,
+        1 => true,
+        b => {
+            return Err(ContractError::InvalidRequestMetadata {
+                reason: format!("is_read_call byte {} is not a bool", b),
+            })
+        }
+    };
+    let asm_address = String::from_utf8(bytes[REQUEST_METADATA_FIXED_LENGTH..].to_vec())
+        .map_err(|_| ContractError::InvalidRequestMetadata {
+            reason: "asm_address is not utf8".to_string(),
+        })?;
+
+    let metadata = RequestMetadata {
+        gas_limit: read_u64(0),
+        gas_price: read_u64(8),
+        ack_gas_limit: read_u64(16),
+        ack_gas_price: read_u64(24),
+        relayer_fees: Uint128::new(u128::from_be_bytes(relayer_fees)),
+        ack_type: bytes[48],
+        is_read_call,
+        asm_address,
+    };
+    validate_request_metadata(&metadata)?;
+    Ok(metadata)
+}
+
+/// resolve_request_metadata picks the packed bytes ISend forwards to the gateway,
+/// either given raw or packed from the structured form
+pub fn resolve_request_metadata(
+    request_metadata: Option<Binary>,
+    metadata: Option<RequestMetadata>,
+) -> Result<Binary, ContractError> {
+    match (request_metadata, metadata) {
+        (Some(request_metadata), None) => Ok(request_metadata),
+        (None, Some(metadata)) => encode_request_metadata(&metadata),
+        (Some(_), Some(_)) => Err(ContractError::InvalidRequestMetadata {
+            reason: "only one of request_metadata and metadata may be set".to_string(),
+        }),
+        (None, None) => Err(ContractError::InvalidRequestMetadata {
+            reason: "one of request_metadata and metadata is required".to_string(),
+        }),
+    }
+}
+
+pub fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+/// inbound_packet_key identifies an inbound packet for replay protection,
+/// by its nonce when the gateway passes one and by its content otherwise
+pub fn inbound_packet_key(
+    src_chain_id: &str,
+    request_sender: &str,
+    packet: &Binary,
+    request_identifier: Option<u64>,
+) -> Vec<u8> {
+    let chain_token = Token::String(src_chain_id.to_string());
+    let sender_token = Token::String(normalize_sender(request_sender));
+    let enc = match request_identifier {
+        Some(nonce) => encode(&[chain_token, sender_token, Token::Uint(U256::from(nonce))]),
+        None => encode(&[chain_token, sender_token, Token::Bytes(packet.to_vec())]),
+    };
+    sha256(&enc)
 }
 
 pub fn abi_decode_to_binary(enc: &Binary) -> Result<Binary, ContractError> {
     let param_types = vec![ParamType::Bytes];
-    let payload = decode(&param_types, enc.as_slice()).or_else(|_| {
-        Err(ContractError::Std(StdError::generic_err("error: abi_decode_to_binary")))
+    let payload = decode(&param_types, enc.as_slice()).map_err(|_| {
+        ContractError::Std(StdError::generic_err("error: abi_decode_to_binary"))
     })?;
     let payload_byte = match payload[0].clone() {
         Token::Bytes(payload) => payload,
@@ -113,10 +241,129 @@ pub fn abi_decode_to_binary(enc: &Binary) -> Result<Binary, ContractError> {
     Ok(Binary::from(payload_byte))
 }
 
-pub fn abi_encode_string(stri: &String) -> Binary {
-    let stri_token = Token::String(stri.clone());
-    let enc = encode(&vec![stri_token]);
-    return Binary::from(enc);
+pub fn abi_encode_string(stri: &str) -> Binary {
+    let stri_token = Token::String(stri.to_string());
+    let enc = encode(&[stri_token]);
+    Binary::from(enc)
+}
+
+pub fn abi_decode_string(enc: &Binary) -> Result<String, ContractError> {
+    let param_types = vec![ParamType::String];
+    let tokens = decode(&param_types, enc.as_slice()).map_err(|_| abi_decode_error("string"))?;
+    match tokens[0].clone() {
+        Token::String(stri) => Ok(stri),
+        _ => Err(abi_decode_error("string")),
+    }
+}
+
+/// abi_encode_ack encodes the ack payload of IReceive as the solidity tuple
+/// (bool success, uint32 error_code, string message)
+pub fn abi_encode_ack(success: bool, error_code: u32, message: &str) -> Binary {
+    let enc = encode(&[
+        Token::Bool(success),
+        Token::Uint(U256::from(error_code)),
+        Token::String(message.to_string()),
+    ]);
+    Binary::from(enc)
+}
+
+/// abi_decode_ack decodes the tuple of abi_encode_ack
+pub fn abi_decode_ack(enc: &Binary) -> Result<(bool, u32, String), ContractError> {
+    let param_types = vec![ParamType::Bool, ParamType::Uint(32), ParamType::String];
+    let tokens = decode(&param_types, enc.as_slice()).map_err(|_| abi_decode_error("ack"))?;
+    match (tokens[0].clone(), tokens[1].clone(), tokens[2].clone()) {
+        (Token::Bool(success), Token::Uint(error_code), Token::String(message)) => {
+            Ok((success, error_code.low_u32(), message))
+        }
+        _ => Err(abi_decode_error("ack")),
+    }
+}
+
+fn abi_decode_error(what: &str) -> ContractError {
+    ContractError::Std(StdError::generic_err(format!("error: abi_decode_{}", what)))
+}
+
+/// abi_encode_resolve_record encodes the solidity tuple (bool found, string address)
+pub fn abi_encode_resolve_record(found: bool, address: &str) -> Binary {
+    let enc = encode(&[Token::Bool(found), Token::String(address.to_string())]);
+    Binary::from(enc)
+}
+
+/// abi_decode_resolve_record decodes the solidity tuple (bool found, string address)
+pub fn abi_decode_resolve_record(enc: &Binary) -> Result<(bool, String), ContractError> {
+    let param_types = vec![ParamType::Bool, ParamType::String];
+    let tokens = decode(&param_types, enc.as_slice())
+        .map_err(|_| abi_decode_error("resolve_record"))?;
+    match (tokens[0].clone(), tokens[1].clone()) {
+        (Token::Bool(found), Token::String(owner)) => Ok((found, owner)),
+        _ => Err(abi_decode_error("resolve_record")),
+    }
+}
+
+// a price is encoded as (bool enabled, string denom, uint256 amount)
+fn price_tokens(price: &Option<Coin>) -> Vec<Token> {
+    match price {
+        Some(coin) => vec![
+            Token::Bool(true),
+            Token::String(coin.denom.clone()),
+            Token::Uint(U256::from(coin.amount.u128())),
+        ],
+        None => vec![Token::Bool(false), Token::String(String::new()), Token::Uint(U256::zero())],
+    }
+}
+
+fn price_from_tokens(tokens: &[Token]) -> Result<Option<Coin>, ContractError> {
+    match (tokens[0].clone(), tokens[1].clone(), tokens[2].clone()) {
+        (Token::Bool(false), _, _) => Ok(None),
+        (Token::Bool(true), Token::String(denom), Token::Uint(amount)) => {
+            if amount > U256::from(u128::MAX) {
+                return Err(abi_decode_error("config"));
+            }
+            Ok(Some(Coin::new(amount.as_u128(), denom)))
+        }
+        _ => Err(abi_decode_error("config")),
+    }
+}
+
+/// abi_encode_config encodes the prices of config as the solidity tuple
+/// (bool has_purchase_price, string purchase_denom, uint256 purchase_amount,
+///  bool has_transfer_price, string transfer_denom, uint256 transfer_amount)
+pub fn abi_encode_config(config: &Config) -> Binary {
+    let mut tokens = price_tokens(&config.purchase_price);
+    tokens.extend(price_tokens(&config.transfer_price));
+    Binary::from(encode(&tokens))
+}
+
+/// abi_decode_config decodes the tuple of abi_encode_config into (purchase_price, transfer_price)
+pub fn abi_decode_config(enc: &Binary) -> Result<(Option<Coin>, Option<Coin>), ContractError> {
+    let price_types = [ParamType::Bool, ParamType::String, ParamType::Uint(256)];
+    let param_types = [price_types.clone(), price_types].concat();
+    let tokens =
+        decode(&param_types, enc.as_slice()).map_err(|_| abi_decode_error("config"))?;
+    Ok((price_from_tokens(&tokens[0..3])?, price_from_tokens(&tokens[3..6])?))
+}
+
+/// lease_price returns the price of registering or renewing for years,
+/// years must be between 1 and MAX_REGISTRATION_YEARS
+pub fn lease_price(price_per_year: Option<Coin>, years: u32) -> Result<Option<Coin>, ContractError> {
+    if years == 0 || years > MAX_REGISTRATION_YEARS {
+        return Err(ContractError::InvalidYears {
+            years,
+            max: MAX_REGISTRATION_YEARS,
+        });
+    }
+    let price = match price_per_year {
+        Some(price) => price,
+        None => return Ok(None),
+    };
+    let amount = price
+        .amount
+        .checked_mul(Uint128::from(years))
+        .map_err(StdError::from)?;
+    Ok(Some(Coin {
+        amount,
+        denom: price.denom,
+    }))
 }
 
 pub fn assert_sent_sufficient_coin(
@@ -142,6 +389,187 @@ pub fn assert_sent_sufficient_coin(
     Ok(())
 }
 
+/// validate_price checks the denom of price follows the cosmos sdk rules:
+/// 3-128 characters, starting with a letter, then letters, numbers or / : . _ -
+pub fn validate_price(price: &Option<Coin>) -> Result<(), ContractError> {
+    let denom = match price {
+        Some(price) => &price.denom,
+        None => return Ok(()),
+    };
+    let valid_length = (MIN_DENOM_LENGTH..=MAX_DENOM_LENGTH).contains(&denom.len());
+    let valid_start = denom.starts_with(|c: char| c.is_ascii_alphabetic());
+    let valid_chars = denom
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | ':' | '.' | '_' | '-'));
+    if !(valid_length && valid_start && valid_chars) {
+        return Err(ContractError::InvalidDenom {
+            denom: denom.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// save_debug_request keeps the last decoded request packet for debug
+pub fn save_debug_request(storage: &mut dyn Storage, request: &Binary) -> Result<(), ContractError> {
+    let mut debug_state = DEBUG_STATE.may_load(storage)?.unwrap_or_default();
+    debug_state.request = Some(request.clone());
+    DEBUG_STATE.save(storage, &debug_state)?;
+    Ok(())
+}
+
+/// save_debug_result keeps the last result for debug
+pub fn save_debug_result(storage: &mut dyn Storage, result: &Binary) -> Result<(), ContractError> {
+    let mut debug_state = DEBUG_STATE.may_load(storage)?.unwrap_or_default();
+    debug_state.result = Some(result.clone());
+    DEBUG_STATE.save(storage, &debug_state)?;
+    Ok(())
+}
+
+/// DryRunStorage reads through to the wrapped storage and keeps its own writes,
+/// which are dropped with it
+pub struct DryRunStorage<'a> {
+    inner: &'a dyn Storage,
+    // None marks a removed key
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> DryRunStorage<'a> {
+    pub fn new(inner: &'a dyn Storage) -> Self {
+        DryRunStorage {
+            inner,
+            writes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<'a> Storage for DryRunStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => value.clone(),
+            None => self.inner.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        let mut merged: BTreeMap<Vec<u8>, Vec<u8>> =
+            self.inner.range(start, end, Order::Ascending).collect();
+        let in_range = |key: &[u8]| {
+            start.is_none_or(|start| key >= start) && end.is_none_or(|end| key < end)
+        };
+        for (key, value) in self.writes.iter().filter(|(key, _)| in_range(key)) {
+            match value {
+                Some(value) => merged.insert(key.clone(), value.clone()),
+                None => merged.remove(key),
+            };
+        }
+        let mut records: Vec<Record> = merged.into_iter().collect();
+        if let Order::Descending = order {
+            records.reverse();
+        }
+        Box::new(records.into_iter())
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
+}
+
+/// assert_gateway returns an error unless the sender is the configured router gateway
+pub fn assert_gateway(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if *sender != config.gateway_address {
+        return Err(ContractError::UnauthorizedGateway {
+            sender: sender.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// assert_admin returns an error unless the sender is the contract admin.
+/// once the admin role is renounced nobody passes
+pub fn assert_admin(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    match ADMIN.may_load(storage)? {
+        Some(admin) if admin == *sender => Ok(()),
+        _ => Err(ContractError::NotAdmin {}),
+    }
+}
+
+/// normalize_sender lowercases hex (evm) addresses, which are case insensitive,
+/// so checksummed and plain forms hit the same allowlist entry
+pub fn normalize_sender(sender: &str) -> String {
+    if sender.starts_with("0x") || sender.starts_with("0X") {
+        sender.to_lowercase()
+    } else {
+        sender.to_string()
+    }
+}
+
+/// assert_trusted_sender returns an error unless (chain_id, sender) is on the allowlist
+pub fn assert_trusted_sender(
+    storage: &dyn Storage,
+    chain_id: &str,
+    sender: &str,
+) -> Result<(), ContractError> {
+    let sender = normalize_sender(sender);
+    if !TRUSTED_SENDERS.has(storage, (chain_id, sender.as_str())) {
+        return Err(ContractError::UntrustedSender {
+            chain_id: chain_id.to_string(),
+            sender,
+        });
+    }
+    Ok(())
+}
+
+/// validate_handler_address checks the handler address has the shape of the chain type
+/// and returns it normalized: evm addresses are 0x followed by 40 hex characters
+pub fn validate_handler_address(
+    chain_type: &ChainType,
+    address: &str,
+) -> Result<String, ContractError> {
+    let invalid = || ContractError::InvalidHandlerAddress {
+        address: address.to_string(),
+    };
+    match chain_type {
+        ChainType::Evm if is_evm_address(address) => Ok(normalize_sender(address)),
+        ChainType::Cosmos if !address.is_empty() && !address.chars().any(char::is_whitespace) => {
+            Ok(address.to_string())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// validate_address checks an account address of the chain type and returns it normalized.
+/// unlike handler addresses, cosmos accounts have to be valid bech32
+pub fn validate_address(chain_type: &ChainType, address: &str) -> Result<String, ContractError> {
+    let invalid = || ContractError::InvalidAddress {
+        address: address.to_string(),
+    };
+    match chain_type {
+        ChainType::Evm if is_evm_address(address) => Ok(normalize_sender(address)),
+        ChainType::Evm => Err(invalid()),
+        ChainType::Cosmos => match bech32::decode(address) {
+            Ok((_, _, Variant::Bech32)) => Ok(address.to_lowercase()),
+            _ => Err(invalid()),
+        },
+    }
+}
+
+// evm addresses are 0x followed by 40 hex characters
+fn is_evm_address(address: &str) -> bool {
+    match address.strip_prefix("0x").or_else(|| address.strip_prefix("0X")) {
+        Some(hex) => hex.len() == EVM_ADDRESS_LENGTH && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
+
 // let's not import a regexp library and just do these checks by hand
 fn invalid_char(c: char) -> bool {
     let is_valid =
@@ -174,6 +602,29 @@ pub fn validate_name(name: &str) -> Result<(), ContractError> {
     }
 }
 
+/// validate_label returns an error if a subdomain label is invalid
+/// (labels have no minimum beyond one character, the full name is checked with validate_name)
+pub fn validate_label(label: &str) -> Result<(), ContractError> {
+    if label.is_empty() {
+        return Err(ContractError::NameTooShort {
+            length: 0,
+            min_length: 1,
+        });
+    }
+    match label.find(|c| c == '.' || invalid_char(c)) {
+        None => Ok(()),
+        Some(bytepos_invalid_char_start) => {
+            let c = label[bytepos_invalid_char_start..].chars().next().unwrap();
+            Err(ContractError::InvalidCharacter { c })
+        }
+    }
+}
+
+/// parent_name returns the name a subdomain belongs to, "alice" for "pay.alice"
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.split_once('.').map(|(_, parent)| parent)
+}
+
 pub fn myprint(stri: String) {
     let new_line = "\n===========================================================\n".to_string();
     print!("{}{}{}", new_line, stri, new_line);
@@ -182,7 +633,7 @@ pub fn myprint(stri: String) {
 #[cfg(test)]
 mod test {
     use super::*;
-    use cosmwasm_std::{coin, coins};
+    use cosmwasm_std::{coin, coins, MemoryStorage};
 
     #[test]
     fn assert_sent_sufficient_coin_works() {
@@ -214,6 +665,109 @@ mod test {
             Err(e) => panic!("Unexpected error: {:?}", e),
         };
     }
+
+    #[test]
+    fn dry_run_storage_works() {
+        let mut storage = MemoryStorage::new();
+        storage.set(b"a", b"1");
+        storage.set(b"b", b"2");
+
+        let mut dry_run = DryRunStorage::new(&storage);
+        dry_run.set(b"c", b"3");
+        dry_run.remove(b"a");
+        assert_eq!(dry_run.get(b"a"), None);
+        assert_eq!(dry_run.get(b"c"), Some(b"3".to_vec()));
+        let keys: Vec<Vec<u8>> = dry_run
+            .range(None, None, Order::Descending)
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(keys, vec![b"c".to_vec(), b"b".to_vec()]);
+
+        // the wrapped storage is untouched
+        assert_eq!(storage.get(b"a"), Some(b"1".to_vec()));
+        assert_eq!(storage.get(b"c"), None);
+    }
+
+    #[test]
+    fn validate_address_works() {
+        let evm = "0x1B1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2A3";
+        assert_eq!(validate_address(&ChainType::Evm, evm).unwrap(), evm.to_lowercase());
+        let cosmos = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu";
+        assert_eq!(validate_address(&ChainType::Cosmos, cosmos).unwrap(), cosmos);
+
+        for (chain_type, address) in [
+            (ChainType::Evm, "1B1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2A3"),
+            (ChainType::Evm, "0x1B1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2"),
+            (ChainType::Evm, cosmos),
+            // bad checksum
+            (ChainType::Cosmos, "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xv"),
+            (ChainType::Cosmos, evm),
+        ] {
+            match validate_address(&chain_type, address) {
+                Ok(_) => panic!("Must return error"),
+                Err(ContractError::InvalidAddress { .. }) => {}
+                Err(e) => panic!("Unexpected error: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn abi_config_round_trip_works() {
+        let config = Config {
+            purchase_price: Some(coin(5, "route")),
+            transfer_price: None,
+            gateway_address: Addr::unchecked("gateway"),
+            reply_failure_policy: crate::state::ReplyFailurePolicy::Strict,
+            max_retry_attempts: 3,
+        };
+        let enc = abi_encode_config(&config);
+        assert_eq!(abi_decode_config(&enc).unwrap(), (Some(coin(5, "route")), None));
+
+        let enc = abi_encode_resolve_record(true, "alice_key");
+        assert_eq!(abi_decode_resolve_record(&enc).unwrap(), (true, "alice_key".to_string()));
+    }
+
+    #[test]
+    fn decode_request_metadata_works() {
+        let metadata = RequestMetadata {
+            gas_limit: 300000,
+            gas_price: 60000000000,
+            ack_gas_limit: 200000,
+            ack_gas_price: 60000000000,
+            relayer_fees: Uint128::new(u128::MAX),
+            ack_type: 3,
+            is_read_call: true,
+            asm_address: "0x1b1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2A3".to_string(),
+        };
+        let encoded = encode_request_metadata(&metadata).unwrap();
+        assert_eq!(decode_request_metadata(&encoded).unwrap(), metadata);
+
+        // truncated metadata
+        let truncated = Binary::from(&encoded.as_slice()[..49]);
+        match decode_request_metadata(&truncated) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidRequestMetadata { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // is_read_call must be 0 or 1
+        let mut bytes = encoded.to_vec();
+        bytes[49] = 2;
+        match decode_request_metadata(&Binary::from(bytes)) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidRequestMetadata { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // asm address must be a printable string
+        let mut bytes = encoded.to_vec();
+        bytes.push(0xff);
+        match decode_request_metadata(&Binary::from(bytes)) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidRequestMetadata { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
 }
 
 #[test]
@@ -1,13 +1,14 @@
-pub mod contract;
-pub mod execute;
-mod error;
-pub mod msg;
-pub mod state;
-pub mod helper;
-pub mod consts;
-pub mod reply;
-
-#[cfg(test)]
-mod tests;
-
-pub use crate::error::ContractError;
+pub mod contract;
+pub mod execute;
+mod error;
+pub mod msg;
+pub mod state;
+pub mod helper;
+pub mod consts;
+pub mod reply;
+pub mod migrate;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::error::ContractError;
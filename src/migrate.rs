@@ -0,0 +1,168 @@
+use cosmwasm_std::{entry_point, Binary, DepsMut, Env, Event, Order, Response, StdResult};
+use cw2::{set_contract_version, CONTRACT};
+use semver::Version;
+
+use crate::consts::{
+    CONTRACT_NAME, CONTRACT_VERSION, DEFAULT_MAX_RETRY_ATTEMPTS, NAME_LEASE_VERSION,
+    NAME_OWNER_INDEX_VERSION, SECONDS_PER_YEAR,
+};
+use crate::msg::{LegacyMigrateMsg, MigrateMsg};
+use crate::state::{
+    name_records, outbound_requests, Config, DebugState, NameRecord, OutboundRequest, Owner,
+    ReplyFailurePolicy, RequestStatus, ADMIN, CONFIG, DEBUG_STATE, LEGACY_CONFIG,
+    LEGACY_NAME_RESOLVER, PENDING, REQUEST, RESULT, UNLEASED_NAME_RESOLVER,
+};
+use crate::ContractError;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // instances deployed before versioning have no stored contract version
+    let previous_version = match CONTRACT.may_load(deps.storage)? {
+        None => {
+            let legacy = msg
+                .legacy
+                .ok_or_else(|| invalid_migration("legacy settings are required"))?;
+            migrate_legacy(deps.branch(), &env, legacy)?;
+            "legacy".to_string()
+        }
+        Some(stored) => {
+            if msg.legacy.is_some() {
+                return Err(invalid_migration("instance is already versioned"));
+            }
+            if stored.contract != CONTRACT_NAME {
+                return Err(invalid_migration(&format!(
+                    "cannot migrate from {}",
+                    stored.contract
+                )));
+            }
+            if parse_version(&stored.version)? > parse_version(CONTRACT_VERSION)? {
+                return Err(ContractError::CannotDowngrade {
+                    previous_version: stored.version,
+                    new_version: CONTRACT_VERSION.to_string(),
+                });
+            }
+            // per-version state transformations go here
+            if parse_version(&stored.version)? < parse_version(NAME_LEASE_VERSION)? {
+                lease_names(deps.branch(), &env)?;
+            }
+            if parse_version(&stored.version)? < parse_version(NAME_OWNER_INDEX_VERSION)? {
+                reindex_names(deps.branch())?;
+            }
+            stored.version
+        }
+    };
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let response = Response::new().add_event(
+        Event::new("Migrate")
+            .add_attribute("previous_version", previous_version)
+            .add_attribute("new_version", CONTRACT_VERSION),
+    );
+    Ok(response)
+}
+
+// migrate_legacy moves the state of an unversioned instance to the current layout
+fn migrate_legacy(deps: DepsMut, env: &Env, msg: LegacyMigrateMsg) -> Result<(), ContractError> {
+    let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+    let config = Config {
+        purchase_price: legacy_config.purchase_price,
+        transfer_price: legacy_config.transfer_price,
+        gateway_address: deps.api.addr_validate(&msg.gateway_address)?,
+        reply_failure_policy: msg
+            .reply_failure_policy
+            .unwrap_or(ReplyFailurePolicy::Strict),
+        max_retry_attempts: msg.max_retry_attempts.unwrap_or(DEFAULT_MAX_RETRY_ATTEMPTS),
+    };
+    CONFIG.save(deps.storage, &config)?;
+    ADMIN.save(deps.storage, &deps.api.addr_validate(&msg.admin)?)?;
+
+    // names were always owned by local addresses and never expired,
+    // they get a one year lease from the migration on
+    let names = LEGACY_NAME_RESOLVER
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (name, record) in names {
+        let record = NameRecord {
+            owner: Owner::local(env, &record.owner),
+            expires: env.block.time.plus_seconds(SECONDS_PER_YEAR),
+        };
+        // both layouts share the namespace, the legacy record can't be read as the old value
+        LEGACY_NAME_RESOLVER.remove(deps.storage, &name);
+        name_records().save(deps.storage, &name, &record)?;
+    }
+
+    // pending requests are tracked by identifier only, the rest of the request was never stored
+    if let Some(pending) = PENDING.may_load(deps.storage)? {
+        for request_identifier in pending.requests {
+            let request = OutboundRequest {
+                version: 0,
+                route_amount: 0,
+                route_recipient: String::new(),
+                dest_chain_id: String::new(),
+                handler_address: String::new(),
+                request_packet: Binary::default(),
+                payload_hash: String::new(),
+                request_metadata: Binary::default(),
+                caller: env.contract.address.clone(),
+                height: env.block.height,
+                time: env.block.time,
+                status: RequestStatus::Pending,
+                retry_of: None,
+                original_request: None,
+                attempt: 0,
+            };
+            outbound_requests().save(deps.storage, request_identifier, &request)?;
+        }
+        PENDING.remove(deps.storage);
+    }
+
+    let debug_state = DebugState {
+        request: REQUEST.may_load(deps.storage)?,
+        result: RESULT.may_load(deps.storage)?,
+    };
+    DEBUG_STATE.save(deps.storage, &debug_state)?;
+    REQUEST.remove(deps.storage);
+    RESULT.remove(deps.storage);
+
+    Ok(())
+}
+
+// names written before leases existed never expired,
+// they get a one year lease from the migration on like legacy names
+fn lease_names(deps: DepsMut, env: &Env) -> Result<(), ContractError> {
+    let names = UNLEASED_NAME_RESOLVER
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (name, record) in names {
+        let record = NameRecord {
+            owner: record.owner,
+            expires: env.block.time.plus_seconds(SECONDS_PER_YEAR),
+        };
+        // both layouts share the namespace, the unleased record can't be read as the old value
+        UNLEASED_NAME_RESOLVER.remove(deps.storage, &name);
+        name_records().save(deps.storage, &name, &record)?;
+    }
+    Ok(())
+}
+
+// names written before the owner index existed are missing from it.
+// this walks every name, so it only runs when migrating from an older version
+fn reindex_names(deps: DepsMut) -> Result<(), ContractError> {
+    let records = name_records()
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (name, record) in records {
+        name_records().replace(deps.storage, &name, Some(&record), None)?;
+    }
+    Ok(())
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|err| invalid_migration(&format!("{}: {}", version, err)))
+}
+
+fn invalid_migration(reason: &str) -> ContractError {
+    ContractError::InvalidMigration {
+        reason: reason.to_string(),
+    }
+}
@@ -1,123 +1,732 @@
-use crate::state::Config;
-use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Binary, Coin};
-
-#[cw_serde]
-pub struct InstantiateMsg {
-    pub purchase_price: Option<Coin>,
-    pub transfer_price: Option<Coin>,
-}
-
-#[cw_serde]
-pub enum ExecuteMsg {
-    ISend {
-        version: u64,
-        route_amount: u64,
-        route_recipient: String,
-        dest_chain_id: String,
-        request_metadata: Binary,
-        gateway_address: String,
-        handler_address: String,
-        payload: Binary,
-    },
-    IReceive {
-        src_chain_id: String,
-        request_sender: String,
-        packet: Binary,
-    },
-    IAck {
-        request_identifier: u64,
-        exec_status: bool,
-        exec_data: Binary,
-    },
-    SetDappMetadata {
-        fee_payer_address: String,
-        gateway_address: String,
-    },
-    Register {
-        name: String,
-    },
-    Transfer {
-        name: String,
-        to: String,
-    },
-}
-
-#[cw_serde]
-pub enum CustomExecuteMsg {
-    Register { name: String },
-    Transfer { name: String, to: String },
-}
-
-#[cw_serde]
-pub enum GatewayMsg {
-    ISend {
-        version: u64,
-        route_amount: u64,
-        route_recipient: String,
-        dest_chain_id: String,
-        request_metadata: Binary,
-        request_packet: Binary,
-    },
-    SetDappMetadata {
-        fee_payer_address: String,
-    },
-}
-
-#[cw_serde]
-#[derive(QueryResponses)]
-pub enum QueryMsg {
-    #[returns(Binary)]
-    IQuery { packet: Binary },
-
-    // following query msgs are used for debug
-    // ResolveAddress returns the current address that the name resolves to
-    #[returns(ResolveRecordResponse)]
-    ResolveRecord { name: String },
-    #[returns(ConfigResponse)]
-    Config {},
-    #[returns(LoadStatesResponse)]
-    LoadStates {},
-}
-
-#[cw_serde]
-pub enum CustomQueryMsg {
-    // ResolveAddress returns the current address that the name resolves to
-    ResolveRecord { name: String },
-    Config {},
-}
-
-// We define a custom struct for each query response
-#[cw_serde]
-pub struct ResolveRecordResponse {
-    pub address: Option<String>,
-}
-
-#[cw_serde]
-pub struct ConfigResponse {
-    pub purchase_price: Option<Coin>,
-    pub transfer_price: Option<Coin>,
-}
-
-impl From<Config> for ConfigResponse {
-    fn from(config: Config) -> ConfigResponse {
-        ConfigResponse {
-            purchase_price: config.purchase_price,
-            transfer_price: config.transfer_price,
-        }
-    }
-}
-
-#[cw_serde]
-pub struct LoadStatesResponse {
-    pub name_resolver: Vec<(String, String)>,
-    pub request: Binary,
-    pub result: Binary,
-    pub nonce: u64,
-    pub pending: Vec<u64>,
-}
-
-#[cw_serde]
-pub struct ResolveResultResponse {
-    pub result: Option<Binary>,
-}
+use crate::state::{
+    BatchEntryResult, ChainType, Config, DappMetadata, FailedRequest, OutboundRequest, Owner,
+    ReplyFailurePolicy, RequestStatus, RetryEntry, RetrySource,
+};
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, Coin, Timestamp, Uint128};
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub purchase_price: Option<Coin>,
+    pub transfer_price: Option<Coin>,
+    pub gateway_address: String,
+    // defaults to ReplyFailurePolicy::Strict
+    pub reply_failure_policy: Option<ReplyFailurePolicy>,
+    // defaults to DEFAULT_MAX_RETRY_ATTEMPTS
+    pub max_retry_attempts: Option<u32>,
+    // defaults to the instantiate sender
+    pub admin: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    // required to migrate an instance deployed before contract versioning,
+    // rejected otherwise
+    pub legacy: Option<LegacyMigrateMsg>,
+}
+
+// settings the legacy layout has no value for
+#[cw_serde]
+pub struct LegacyMigrateMsg {
+    pub gateway_address: String,
+    pub admin: String,
+    // default to the same values as InstantiateMsg
+    pub reply_failure_policy: Option<ReplyFailurePolicy>,
+    pub max_retry_attempts: Option<u32>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    // admin only: exactly one of request_metadata (packed bytes) and metadata (packed by the
+    // contract) is required. packets go out as this contract, which remote handlers trust
+    ISend {
+        version: u64,
+        route_amount: u64,
+        route_recipient: String,
+        dest_chain_id: String,
+        request_metadata: Option<Binary>,
+        metadata: Option<RequestMetadata>,
+        handler_address: String,
+        payload: Binary,
+    },
+    // admin only: send one packet per entry through the config gateway.
+    // allow_partial_failure records failed entries instead of reverting the whole batch
+    ISendBatch {
+        requests: Vec<ISendRequest>,
+        allow_partial_failure: bool,
+    },
+    // admin only: send payload to a registered chain, handler address and metadata default to the
+    // chain registry and can be overridden per call
+    ISendTo {
+        dest_chain_id: String,
+        payload: Binary,
+        handler_address: Option<String>,
+        metadata: Option<RequestMetadata>,
+        route_amount: Option<u64>,
+        route_recipient: Option<String>,
+    },
+    // request_identifier is the nonce of the packet when the gateway passes one,
+    // otherwise packets are deduplicated by their content
+    IReceive {
+        src_chain_id: String,
+        request_sender: String,
+        packet: Binary,
+        request_identifier: Option<u64>,
+    },
+    // contract only: runs the handler of an IReceive packet as a submessage,
+    // so that its writes are reverted when it fails
+    HandleIReceive {
+        caller: Owner,
+        payload: Binary,
+    },
+    IAck {
+        request_identifier: u64,
+        exec_status: bool,
+        exec_data: Binary,
+    },
+    // admin only: set the fee payer of this contract on the gateway
+    SetDappMetadata {
+        fee_payer_address: String,
+    },
+    // years of registration, defaults to 1
+    Register {
+        name: String,
+        years: Option<u32>,
+    },
+    // extend the lease of a name, anyone can pay for it.
+    // subdomains are free and catch up with the lease of their parent, years is ignored
+    Renew {
+        name: String,
+        years: u32,
+    },
+    // to_chain_id defaults to the chain of the current owner, other chains have to be registered
+    Transfer {
+        name: String,
+        to: String,
+        to_chain_id: Option<String>,
+    },
+    // owner only: set the address the name resolves to on chain_id,
+    // which is the local chain or a registered destination chain
+    SetTarget {
+        name: String,
+        chain_id: String,
+        address: String,
+    },
+    // owner only
+    RemoveTarget {
+        name: String,
+        chain_id: String,
+    },
+    // parent owner only: create label.parent for owner, owner_chain_id defaults to the sender's chain
+    CreateSubdomain {
+        parent: String,
+        label: String,
+        owner: String,
+        owner_chain_id: Option<String>,
+    },
+    // parent owner only: take a subdomain back from its owner, it is removed with its records
+    ReclaimSubdomain {
+        name: String,
+    },
+    // owner only: the name the sender's address reverse resolves to
+    SetPrimaryName {
+        name: String,
+    },
+    // cw721: token_id is the name, the transfer price applies as for Transfer
+    TransferNft {
+        recipient: String,
+        token_id: String,
+    },
+    // cw721: TransferNft to a contract, which is called with ReceiveNft
+    SendNft {
+        contract: String,
+        token_id: String,
+        msg: Binary,
+    },
+    // cw721, owner only: let spender transfer or send the name, expires defaults to never
+    Approve {
+        spender: String,
+        token_id: String,
+        expires: Option<Expiration>,
+    },
+    // cw721, owner only
+    Revoke {
+        spender: String,
+        token_id: String,
+    },
+    // auction a name nobody holds, bids are paid in the denom of min_bid.
+    // the attached funds are the opening bid and must cover min_bid
+    StartAuction {
+        name: String,
+        min_bid: Coin,
+    },
+    // bid the attached funds, the outbid bidder is refunded
+    Bid {
+        name: String,
+    },
+    // anyone can settle an auction once it has ended
+    SettleAuction {
+        name: String,
+    },
+    // admin only: propose a new admin, who has to accept before taking over
+    ProposeAdmin {
+        admin: String,
+    },
+    // proposed admin only: take over the admin role
+    AcceptAdmin {},
+    // admin only: give up the admin role for good, privileged messages are disabled afterwards
+    RenounceAdmin {},
+    // admin only: replace the prices, None disables a price
+    UpdateConfig {
+        purchase_price: Option<Coin>,
+        transfer_price: Option<Coin>,
+    },
+    // admin only: point the contract at a new router gateway
+    UpdateGateway {
+        gateway_address: String,
+    },
+    // admin only: choose how failed gateway ISend submessages are handled
+    SetReplyFailurePolicy {
+        policy: ReplyFailurePolicy,
+    },
+    // resend queued failed requests, optionally with bumped gas/fee metadata.
+    // only the original caller or the admin can retry a request
+    Retry {
+        ids: Vec<u64>,
+        metadata: Option<RequestMetadata>,
+    },
+    // admin only: set how many times a failed request can be retried
+    SetMaxRetryAttempts {
+        max_retry_attempts: u32,
+    },
+    // admin only: register or update the defaults of a destination chain
+    SetChain {
+        dest_chain_id: String,
+        chain_type: ChainType,
+        handler_address: String,
+        metadata: RequestMetadata,
+    },
+    // admin only: remove a destination chain from the registry
+    RemoveChain {
+        dest_chain_id: String,
+    },
+    // admin only: allow a remote handler contract to send packets from chain_id
+    AddTrustedSender {
+        chain_id: String,
+        sender: String,
+    },
+    // admin only: revoke a remote handler contract
+    RemoveTrustedSender {
+        chain_id: String,
+        sender: String,
+    },
+}
+
+// RequestMetadata is the structured form of the packed request_metadata of ISend
+#[cw_serde]
+pub struct RequestMetadata {
+    pub gas_limit: u64,
+    pub gas_price: u64,
+    pub ack_gas_limit: u64,
+    pub ack_gas_price: u64,
+    pub relayer_fees: Uint128,
+    pub ack_type: u8,
+    pub is_read_call: bool,
+    pub asm_address: String,
+}
+
+// ISendRequest is one entry of ISendBatch, fields are the same as ISend
+#[cw_serde]
+pub struct ISendRequest {
+    pub version: u64,
+    pub route_amount: u64,
+    pub route_recipient: String,
+    pub dest_chain_id: String,
+    pub request_metadata: Option<Binary>,
+    pub metadata: Option<RequestMetadata>,
+    pub handler_address: String,
+    pub payload: Binary,
+}
+
+#[cw_serde]
+pub enum CustomExecuteMsg {
+    Register { name: String, years: Option<u32> },
+    Renew { name: String, years: u32 },
+    Transfer {
+        name: String,
+        to: String,
+        to_chain_id: Option<String>,
+    },
+    SetTarget {
+        name: String,
+        chain_id: String,
+        address: String,
+    },
+    RemoveTarget { name: String, chain_id: String },
+    CreateSubdomain {
+        parent: String,
+        label: String,
+        owner: String,
+        owner_chain_id: Option<String>,
+    },
+    ReclaimSubdomain { name: String },
+    SetPrimaryName { name: String },
+    Bid { name: String },
+    // pays out refunds of the remote sender to a local recipient
+    ClaimCredits { recipient: String },
+}
+
+#[cw_serde]
+pub enum GatewayMsg {
+    ISend {
+        version: u64,
+        route_amount: u64,
+        route_recipient: String,
+        dest_chain_id: String,
+        request_metadata: Binary,
+        request_packet: Binary,
+    },
+    SetDappMetadata {
+        fee_payer_address: String,
+    },
+}
+
+// cw721 receiver hook, executed on the recipient contract of SendNft
+#[cw_serde]
+pub enum ReceiverExecuteMsg {
+    ReceiveNft(Cw721ReceiveMsg),
+}
+
+#[cw_serde]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(Binary)]
+    IQuery { packet: Binary },
+
+    // following query msgs are used for debug
+    // ResolveAddress returns the current owner of the name,
+    // and the target address on chain_id if it is given
+    #[returns(ResolveRecordResponse)]
+    ResolveRecord {
+        name: String,
+        chain_id: Option<String>,
+    },
+    // ReverseResolve returns the primary name of an address, chain_id defaults to this chain
+    #[returns(ReverseResolveResponse)]
+    ReverseResolve {
+        address: String,
+        chain_id: Option<String>,
+    },
+    // cw721 queries, token ids are names. expired names are left out
+    #[returns(OwnerOfResponse)]
+    OwnerOf {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    #[returns(NftInfoResponse)]
+    NftInfo { token_id: String },
+    // Tokens lists the names of a local owner. a page scans at most limit names and
+    // leaves out the expired ones, so it can hold fewer than limit tokens
+    #[returns(TokensResponse)]
+    Tokens {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // AllTokens pages the same way as Tokens
+    #[returns(TokensResponse)]
+    AllTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Subdomains lists the subdomains of a name, start_after is a label
+    #[returns(SubdomainsResponse)]
+    Subdomains {
+        parent: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ConfigResponse)]
+    Config {},
+    #[returns(LoadStatesResponse)]
+    LoadStates {},
+    // Admin returns the current and proposed admin
+    #[returns(AdminResponse)]
+    Admin {},
+    // TrustedSenders lists the remote handler contracts allowed on chain_id
+    #[returns(TrustedSendersResponse)]
+    TrustedSenders {
+        chain_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(IsTrustedSenderResponse)]
+    IsTrustedSender { chain_id: String, sender: String },
+    // Auction returns a running or ended, unsettled auction
+    #[returns(AuctionResponse)]
+    Auction { name: String },
+    // Credits returns refunds a remote account can claim through ClaimCredits
+    #[returns(CreditsResponse)]
+    Credits { chain_id: String, address: String },
+    // Chain returns the registry entry of a destination chain
+    #[returns(ChainResponse)]
+    Chain { dest_chain_id: String },
+    #[returns(ChainsResponse)]
+    Chains {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // OutboundRequest returns an outbound request sent through ISend
+    #[returns(OutboundRequestResponse)]
+    OutboundRequest { request_identifier: u64 },
+    #[returns(OutboundRequestsResponse)]
+    OutboundRequestsByStatus {
+        status: RequestStatus,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(OutboundRequestsResponse)]
+    OutboundRequestsByChain {
+        dest_chain_id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // RequestResult returns the ack of an outbound request
+    #[returns(ResolveResultResponse)]
+    RequestResult { request_identifier: u64 },
+    // OutboundRequestMetadata decodes the metadata that went out with an outbound request
+    #[returns(RequestMetadata)]
+    OutboundRequestMetadata { request_identifier: u64 },
+    // DecodeRequestMetadata decodes packed request metadata
+    #[returns(RequestMetadata)]
+    DecodeRequestMetadata { request_metadata: Binary },
+    // FailedRequest returns an outbound request the gateway rejected
+    #[returns(FailedRequestResponse)]
+    FailedRequest { id: u64 },
+    #[returns(FailedRequestsResponse)]
+    FailedRequests {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // RetryEntry returns a failed request waiting in the retry queue
+    #[returns(RetryEntryResponse)]
+    RetryEntry { id: u64 },
+    #[returns(RetryQueueResponse)]
+    RetryQueue {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // DappMetadata returns the fee payer configured on the gateway
+    #[returns(DappMetadataResponse)]
+    DappMetadata {},
+    // PacketProcessed reports whether an inbound packet has already been executed
+    #[returns(PacketProcessedResponse)]
+    PacketProcessed {
+        src_chain_id: String,
+        request_sender: String,
+        packet: Binary,
+        request_identifier: Option<u64>,
+    },
+}
+
+#[cw_serde]
+pub enum CustomQueryMsg {
+    // ResolveAddress returns the current owner of the name,
+    // or its target address on chain_id if it is given
+    ResolveRecord {
+        name: String,
+        chain_id: Option<String>,
+    },
+    // ReverseResolve returns the primary name of an address, chain_id defaults to this chain
+    ReverseResolve {
+        address: String,
+        chain_id: Option<String>,
+    },
+    Config {},
+}
+
+// We define a custom struct for each query response
+#[cw_serde]
+pub struct ResolveRecordResponse {
+    pub address: Option<String>,
+    // chain the owner address lives on
+    pub chain_id: Option<String>,
+    pub expires: Option<Timestamp>,
+    // address the name resolves to on the requested chain
+    pub target: Option<String>,
+}
+
+#[cw_serde]
+pub struct ReverseResolveResponse {
+    pub name: Option<String>,
+}
+
+#[cw_serde]
+pub struct Approval {
+    pub spender: String,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct OwnerOfResponse {
+    pub owner: String,
+    // chain the owner address lives on
+    pub owner_chain_id: String,
+    pub approvals: Vec<Approval>,
+}
+
+#[cw_serde]
+pub struct NameExtension {
+    // chain the owner address lives on
+    pub owner_chain_id: String,
+    pub expires: Timestamp,
+}
+
+#[cw_serde]
+pub struct NftInfoResponse {
+    pub token_uri: Option<String>,
+    pub extension: NameExtension,
+}
+
+#[cw_serde]
+pub struct TokensResponse {
+    pub tokens: Vec<String>,
+}
+
+#[cw_serde]
+pub struct SubdomainsResponse {
+    // full names, such as pay.alice
+    pub subdomains: Vec<String>,
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub purchase_price: Option<Coin>,
+    pub transfer_price: Option<Coin>,
+    pub gateway_address: String,
+    pub reply_failure_policy: ReplyFailurePolicy,
+    pub max_retry_attempts: u32,
+}
+
+impl From<Config> for ConfigResponse {
+    fn from(config: Config) -> ConfigResponse {
+        ConfigResponse {
+            purchase_price: config.purchase_price,
+            transfer_price: config.transfer_price,
+            gateway_address: config.gateway_address.to_string(),
+            reply_failure_policy: config.reply_failure_policy,
+            max_retry_attempts: config.max_retry_attempts,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct AdminResponse {
+    // None once the admin role is renounced
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+}
+
+#[cw_serde]
+pub struct LoadStatesResponse {
+    pub name_resolver: Vec<(String, String)>,
+    pub request: Binary,
+    pub result: Binary,
+    pub nonce: u64,
+    pub pending: Vec<u64>,
+}
+
+#[cw_serde]
+pub struct ResolveResultResponse {
+    pub request_identifier: u64,
+    // both are None until the ack for request_identifier arrives
+    pub exec_status: Option<bool>,
+    pub result: Option<Binary>,
+}
+
+#[cw_serde]
+pub struct TrustedSendersResponse {
+    pub chain_id: String,
+    pub senders: Vec<String>,
+}
+
+#[cw_serde]
+pub struct IsTrustedSenderResponse {
+    pub trusted: bool,
+}
+
+#[cw_serde]
+pub struct OutboundRequestResponse {
+    pub request_identifier: u64,
+    pub dest_chain_id: String,
+    pub handler_address: String,
+    pub payload_hash: String,
+    pub caller: String,
+    pub height: u64,
+    pub time: Timestamp,
+    pub status: RequestStatus,
+    pub retry_of: Option<u64>,
+    pub original_request: Option<RetrySource>,
+    pub attempt: u32,
+}
+
+impl From<(u64, OutboundRequest)> for OutboundRequestResponse {
+    fn from((request_identifier, request): (u64, OutboundRequest)) -> OutboundRequestResponse {
+        OutboundRequestResponse {
+            request_identifier,
+            dest_chain_id: request.dest_chain_id,
+            handler_address: request.handler_address,
+            payload_hash: request.payload_hash,
+            caller: request.caller.to_string(),
+            height: request.height,
+            time: request.time,
+            status: request.status,
+            retry_of: request.retry_of,
+            original_request: request.original_request,
+            attempt: request.attempt,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct OutboundRequestsResponse {
+    pub requests: Vec<OutboundRequestResponse>,
+}
+
+#[cw_serde]
+pub struct PacketProcessedResponse {
+    pub processed: bool,
+    // block height the packet was executed at
+    pub height: Option<u64>,
+}
+
+#[cw_serde]
+pub struct DappMetadataResponse {
+    // None until the gateway accepted a SetDappMetadata
+    pub fee_payer_address: Option<String>,
+    pub gateway_response: Option<Binary>,
+}
+
+impl From<Option<DappMetadata>> for DappMetadataResponse {
+    fn from(dapp_metadata: Option<DappMetadata>) -> DappMetadataResponse {
+        match dapp_metadata {
+            Some(dapp_metadata) => DappMetadataResponse {
+                fee_payer_address: Some(dapp_metadata.fee_payer_address),
+                gateway_response: dapp_metadata.gateway_response,
+            },
+            None => DappMetadataResponse {
+                fee_payer_address: None,
+                gateway_response: None,
+            },
+        }
+    }
+}
+
+#[cw_serde]
+pub struct FailedRequestResponse {
+    pub id: u64,
+    pub dest_chain_id: String,
+    pub handler_address: String,
+    pub payload_hash: String,
+    pub caller: String,
+    pub height: u64,
+    pub time: Timestamp,
+    pub error: String,
+}
+
+impl From<(u64, FailedRequest)> for FailedRequestResponse {
+    fn from((id, failed): (u64, FailedRequest)) -> FailedRequestResponse {
+        FailedRequestResponse {
+            id,
+            dest_chain_id: failed.request.dest_chain_id,
+            handler_address: failed.request.handler_address,
+            payload_hash: failed.request.payload_hash,
+            caller: failed.request.caller.to_string(),
+            height: failed.request.height,
+            time: failed.request.time,
+            error: failed.error,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct FailedRequestsResponse {
+    pub requests: Vec<FailedRequestResponse>,
+}
+
+#[cw_serde]
+pub struct RetryEntryResponse {
+    pub id: u64,
+    pub dest_chain_id: String,
+    pub handler_address: String,
+    pub request_packet: Binary,
+    pub request_metadata: Binary,
+    pub caller: String,
+    // number of retries already made
+    pub attempt: u32,
+    pub source: RetrySource,
+}
+
+impl From<(u64, RetryEntry)> for RetryEntryResponse {
+    fn from((id, entry): (u64, RetryEntry)) -> RetryEntryResponse {
+        RetryEntryResponse {
+            id,
+            dest_chain_id: entry.request.dest_chain_id,
+            handler_address: entry.request.handler_address,
+            request_packet: entry.request.request_packet,
+            request_metadata: entry.request.request_metadata,
+            caller: entry.request.caller.to_string(),
+            attempt: entry.request.attempt,
+            source: entry.source,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct RetryQueueResponse {
+    pub entries: Vec<RetryEntryResponse>,
+}
+
+// data of a successful ISendBatch, entries are in request order
+#[cw_serde]
+pub struct ISendBatchResponse {
+    pub entries: Vec<BatchEntryResult>,
+}
+
+#[cw_serde]
+pub struct ChainResponse {
+    pub dest_chain_id: String,
+    pub chain_type: ChainType,
+    pub handler_address: String,
+    pub metadata: RequestMetadata,
+}
+
+#[cw_serde]
+pub struct ChainsResponse {
+    pub chains: Vec<ChainResponse>,
+}
+
+#[cw_serde]
+pub struct AuctionResponse {
+    pub name: String,
+    pub min_bid: Coin,
+    pub end: Timestamp,
+    pub ended: bool,
+    pub highest_bidder: Option<String>,
+    // chain the highest bidder lives on
+    pub highest_bidder_chain_id: Option<String>,
+    pub highest_bid: Option<Coin>,
+}
+
+#[cw_serde]
+pub struct CreditsResponse {
+    pub credits: Vec<Coin>,
+}
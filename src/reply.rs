@@ -1,34 +1,67 @@
-use cw_utils::{parse_reply_execute_data, ParseReplyError};
-use cosmwasm_std::{DepsMut, Env, Reply, Response, StdError, entry_point, to_binary, from_binary};
+use cw_utils::{parse_execute_response_data, parse_reply_execute_data, ParseReplyError};
+use cosmwasm_std::{
+    DepsMut, Env, Event, Reply, Response, StdError, Storage, SubMsgResult, entry_point, to_binary,
+    from_binary,
+};
 
-use crate::{consts::ISEND_ID, ContractError, state::{PENDING, PendingRequests}};
+use crate::{
+    consts::{ISEND_ID, I_RECEIVE_ID, SET_DAPP_METADATA_ID},
+    ContractError,
+    state::{
+        outbound_requests, BatchEntryResult, DappMetadata, FailedRequest, OutboundRequest,
+        PendingBatch, ReplyFailurePolicy, RequestStatus, RetrySource, CONFIG, DAPP_METADATA,
+        FAILED_REQUESTS, FAILED_REQUEST_SEQ, PENDING_BATCH, PENDING_FEE_PAYER, PENDING_I_RECEIVE,
+        PENDING_SENDS,
+    },
+    helper::{abi_decode_string, abi_encode_ack},
+    msg::ISendBatchResponse,
+    execute::{add_credits, enqueue_retry, replay_i_receive},
+};
 
 #[entry_point]
-pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
     // NONCE.save(deps.storage, &reply.id)?;
     // REQUEST.save(deps.storage, &to_binary(&reply)?)?;
     // Ok(Response::new())
     match reply.id {
         ISEND_ID => handle_i_send_reply(deps, reply),
+        SET_DAPP_METADATA_ID => handle_set_dapp_metadata_reply(deps, reply),
+        I_RECEIVE_ID => handle_i_receive_reply(deps, env, reply),
         _ => {
             let err = StdError::generic_err("=========================invalid reply id==========================");
-            return Err(ContractError::Std(err));
+            Err(ContractError::Std(err))
         }
     }
 }
 
 fn handle_i_send_reply(deps: DepsMut, reply: Reply) -> Result<Response, ContractError> {
+    let request = pop_pending_send(deps.storage)?;
+    let batch = PENDING_BATCH.may_load(deps.storage)?;
     let execute_response = parse_reply_execute_data(reply);
-    let request_identifier: u64;
     match execute_response {
         Ok(ok_resp) => {
-            request_identifier = from_binary(&ok_resp.data.unwrap())?;
-            let pending_requests: PendingRequests = PENDING.load(deps.storage)
-                .unwrap_or(PendingRequests { requests: vec![] });
-            let mut requests = pending_requests.requests.clone();
-            requests.push(request_identifier);
-            let new_pending_requests = PendingRequests {requests: requests};
-            PENDING.save(deps.storage, &new_pending_requests)?;
+            let data = ok_resp.data.ok_or_else(|| {
+                StdError::generic_err("handle_i_send_reply: missing request_identifier")
+            })?;
+            let request_identifier: u64 = from_binary(&data)?;
+
+            // the request queued by execute_i_send is now tracked under its identifier
+            let dest_chain_id = request.dest_chain_id.clone();
+            outbound_requests().save(deps.storage, request_identifier, &request)?;
+
+            let response = Response::new().set_data(to_binary(&format!("handle_i_send_reply, request_identifier: {}", request_identifier))?);
+            match batch {
+                Some(batch) => {
+                    let result = BatchEntryResult {
+                        index: batch.entries.len() as u32,
+                        dest_chain_id,
+                        request_identifier: Some(request_identifier),
+                        failed_request_id: None,
+                    };
+                    track_batch_entry(deps.storage, batch, result, response)
+                }
+                None => Ok(response),
+            }
         },
         Err(err) =>  {
             let err_str = match err {
@@ -36,10 +69,165 @@ fn handle_i_send_reply(deps: DepsMut, reply: Reply) -> Result<Response, Contract
                 ParseReplyError::ParseFailure(str2) => format!("ParseFailure: {}", str2),
                 ParseReplyError::BrokenUtf8(_) => "BrokenUtf8".to_string(),
             };
-            let err = StdError::generic_err(err_str);
+            // batches choose their own failure mode, single sends follow the config policy
+            let record = match &batch {
+                Some(batch) => batch.allow_partial_failure,
+                None => {
+                    let config = CONFIG.load(deps.storage)?;
+                    config.reply_failure_policy == ReplyFailurePolicy::Record
+                }
+            };
+            if !record {
+                return Err(ContractError::Std(StdError::generic_err(err_str)));
+            }
+
+            let dest_chain_id = request.dest_chain_id.clone();
+            let (failed_request_id, response) = record_failed_request(deps.storage, request, err_str)?;
+            match batch {
+                Some(batch) => {
+                    let result = BatchEntryResult {
+                        index: batch.entries.len() as u32,
+                        dest_chain_id,
+                        request_identifier: None,
+                        failed_request_id: Some(failed_request_id),
+                    };
+                    track_batch_entry(deps.storage, batch, result, response)
+                }
+                None => Ok(response),
+            }
+        }
+    }
+}
+
+// track_batch_entry adds the outcome of one batch entry. the reply of the last
+// entry replaces the ISendBatch data with the outcome of every entry
+fn track_batch_entry(
+    storage: &mut dyn Storage,
+    mut batch: PendingBatch,
+    result: BatchEntryResult,
+    response: Response,
+) -> Result<Response, ContractError> {
+    batch.entries.push(result);
+    batch.remaining -= 1;
+    if batch.remaining > 0 {
+        PENDING_BATCH.save(storage, &batch)?;
+        return Ok(response);
+    }
+    PENDING_BATCH.remove(storage);
+
+    let failed = batch
+        .entries
+        .iter()
+        .filter(|entry| entry.failed_request_id.is_some())
+        .count();
+    let event = Event::new("ISendBatch")
+        .add_attribute("sent", (batch.entries.len() - failed).to_string())
+        .add_attribute("failed", failed.to_string());
+    let data = to_binary(&ISendBatchResponse {
+        entries: batch.entries,
+    })?;
+    Ok(response.add_event(event).set_data(data))
+}
+
+fn handle_set_dapp_metadata_reply(deps: DepsMut, reply: Reply) -> Result<Response, ContractError> {
+    let fee_payer_address = PENDING_FEE_PAYER.load(deps.storage)?;
+    PENDING_FEE_PAYER.remove(deps.storage);
+
+    // the gateway may answer without data, which is not an error
+    let gateway_response = match reply.result {
+        SubMsgResult::Ok(resp) => match resp.data {
+            Some(data) => parse_execute_response_data(data.as_slice())
+                .map_err(|err| StdError::generic_err(format!("ParseFailure: {}", err)))?
+                .data,
+            None => None,
+        },
+        SubMsgResult::Err(err) => {
+            let err = StdError::generic_err(format!("SubMsgFailure: {}", err));
             return Err(ContractError::Std(err));
         }
+    };
+
+    let dapp_metadata = DappMetadata {
+        fee_payer_address: fee_payer_address.clone(),
+        gateway_response,
+    };
+    DAPP_METADATA.save(deps.storage, &dapp_metadata)?;
+
+    let response = Response::new()
+        .add_event(Event::new("SetDappMetadata").add_attribute("fee_payer_address", fee_payer_address));
+    Ok(response)
+}
+
+// handle_i_receive_reply turns the outcome of an IReceive handler into the ack for the
+// source chain. a failed handler has been reverted, so only its funds are kept here
+fn handle_i_receive_reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
+    let pending = PENDING_I_RECEIVE.load(deps.storage)?;
+    PENDING_I_RECEIVE.remove(deps.storage);
+
+    let error = match reply.result.clone() {
+        SubMsgResult::Ok(_) => {
+            let data = parse_reply_execute_data(reply)
+                .map_err(|err| StdError::generic_err(format!("ParseFailure: {}", err)))?
+                .data;
+            let message = match data {
+                Some(data) => abi_decode_string(&data).unwrap_or_default(),
+                None => String::new(),
+            };
+            return Ok(Response::new().set_data(abi_encode_ack(true, 0, &message)));
+        }
+        // the chain only passes a redacted error string, the replay recovers the error code
+        SubMsgResult::Err(err) => replay_i_receive(deps.as_ref(), &env, &pending)
+            .unwrap_or_else(|| ContractError::Std(StdError::generic_err(err))),
+    };
+
+    // funds that came with the failed packet can be claimed back by the sender
+    if !pending.funds.is_empty() {
+        add_credits(deps.storage, &pending.caller, &pending.funds)?;
     }
-    let response = Response::new().set_data(to_binary(&format!("handle_i_send_reply, request_identifier: {}", request_identifier))?);
+    let error_code = error.code();
+    let message = error.to_string();
+    let response = Response::new()
+        .add_event(
+            Event::new("IReceiveFailed")
+                .add_attribute("error_code", error_code.to_string())
+                .add_attribute("error", message.clone()),
+        )
+        .set_data(abi_encode_ack(false, error_code, &message));
     Ok(response)
-}
\ No newline at end of file
+}
+
+fn pop_pending_send(storage: &mut dyn Storage) -> Result<OutboundRequest, ContractError> {
+    PENDING_SENDS.pop_front(storage)?.ok_or_else(|| {
+        ContractError::Std(StdError::generic_err("handle_i_send_reply: no pending request"))
+    })
+}
+
+// record_failed_request keeps the request the gateway rejected, so the caller's
+// transaction can succeed while the failure stays visible and retryable
+fn record_failed_request(
+    storage: &mut dyn Storage,
+    mut request: OutboundRequest,
+    error: String,
+) -> Result<(u64, Response), ContractError> {
+    request.status = RequestStatus::SendFailure;
+
+    let id = FAILED_REQUEST_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    FAILED_REQUEST_SEQ.save(storage, &id)?;
+    let failed = FailedRequest {
+        request: request.clone(),
+        error: error.clone(),
+    };
+    FAILED_REQUESTS.save(storage, id, &failed)?;
+
+    let source = RetrySource::SendFailure {
+        failed_request_id: id,
+    };
+    let retry_id = enqueue_retry(storage, request.clone(), source)?;
+
+    let event = Event::new("ISendFailed")
+        .add_attribute("failed_request_id", id.to_string())
+        .add_attribute("retry_id", retry_id.to_string())
+        .add_attribute("dest_chain_id", request.dest_chain_id)
+        .add_attribute("error", error);
+    Ok((id, Response::new().add_event(event)))
+}
@@ -1,28 +1,341 @@
-use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Binary};
-use cw_storage_plus::{Item, Map};
-
-#[cw_serde]
-pub struct Config {
-    pub purchase_price: Option<Coin>,
-    pub transfer_price: Option<Coin>,
-}
-
-#[cw_serde]
-pub struct NameRecord {
-    pub owner: Addr,
-}
-
-#[cw_serde]
-pub struct PendingRequests {
-    pub requests: Vec<u64>
-}
-
-pub const CONFIG: Item<Config> = Item::new("config");
-pub const NAME_RESOLVER: Map<&[u8], NameRecord> = Map::new("name_resolver");
-
-pub const REQUEST: Item<Binary> = Item::new("request");
-pub const RESULT: Item<Binary> = Item::new("result");
-pub const NONCE: Item<u64> = Item::new("nonce");
-
-pub const PENDING: Item<PendingRequests> = Item::new("pending");
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Coin, Binary, Empty, Env, Timestamp};
+use cw_storage_plus::{Deque, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::Expiration;
+
+use crate::consts::GRACE_PERIOD_SECONDS;
+
+#[cw_serde]
+pub struct Config {
+    // price of one year of registration
+    pub purchase_price: Option<Coin>,
+    pub transfer_price: Option<Coin>,
+    // router gateway contract, the only sender allowed to deliver IReceive and IAck
+    pub gateway_address: Addr,
+    pub reply_failure_policy: ReplyFailurePolicy,
+    // how many times a failed outbound request may be retried
+    pub max_retry_attempts: u32,
+}
+
+// what to do when a gateway ISend submessage fails
+#[cw_serde]
+pub enum ReplyFailurePolicy {
+    // fail the whole transaction
+    Strict,
+    // record the failed request and let the transaction succeed
+    Record,
+}
+
+// Owner identifies who controls a name: a local account has the chain_id of this chain,
+// a remote one has the src_chain_id its packets come from
+#[cw_serde]
+pub struct Owner {
+    pub chain_id: String,
+    pub address: String,
+}
+
+impl Owner {
+    pub fn local(env: &Env, address: &Addr) -> Owner {
+        Owner {
+            chain_id: env.block.chain_id.clone(),
+            address: address.to_string(),
+        }
+    }
+
+    pub fn is_local(&self, env: &Env) -> bool {
+        self.chain_id == env.block.chain_id
+    }
+}
+
+#[cw_serde]
+pub struct NameRecord {
+    pub owner: Owner,
+    // end of the lease, the name stops resolving afterwards
+    pub expires: Timestamp,
+}
+
+impl NameRecord {
+    pub fn is_expired(&self, env: &Env) -> bool {
+        env.block.time >= self.expires
+    }
+
+    // the name can be registered again once the grace period is over
+    pub fn is_released(&self, env: &Env) -> bool {
+        env.block.time >= self.expires.plus_seconds(GRACE_PERIOD_SECONDS)
+    }
+}
+
+#[cw_serde]
+pub struct Bid {
+    pub bidder: Owner,
+    pub amount: Coin,
+}
+
+// auction for an available name, the highest bid at the deadline wins a one year lease
+#[cw_serde]
+pub struct Auction {
+    pub min_bid: Coin,
+    pub end: Timestamp,
+    pub highest_bid: Option<Bid>,
+}
+
+// legacy layout of outbound requests, superseded by OUTBOUND_REQUESTS
+#[cw_serde]
+pub struct PendingRequests {
+    pub requests: Vec<u64>
+}
+
+// legacy layout of Config, before the gateway and retry settings
+#[cw_serde]
+pub struct LegacyConfig {
+    pub purchase_price: Option<Coin>,
+    pub transfer_price: Option<Coin>,
+}
+
+// legacy layout of NameRecord, owners were always local
+#[cw_serde]
+pub struct LegacyNameRecord {
+    pub owner: Addr,
+}
+
+// layout of NameRecord before leases, names never expired
+#[cw_serde]
+pub struct UnleasedNameRecord {
+    pub owner: Owner,
+}
+
+// last request and result handled by the contract, kept for debug
+#[cw_serde]
+#[derive(Default)]
+pub struct DebugState {
+    pub request: Option<Binary>,
+    pub result: Option<Binary>,
+}
+
+#[cw_serde]
+pub enum RequestStatus {
+    // the gateway rejected the ISend submessage
+    SendFailure,
+    Pending,
+    AckSuccess,
+    AckFailure,
+}
+
+impl RequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestStatus::SendFailure => "send_failure",
+            RequestStatus::Pending => "pending",
+            RequestStatus::AckSuccess => "ack_success",
+            RequestStatus::AckFailure => "ack_failure",
+        }
+    }
+}
+
+#[cw_serde]
+pub struct OutboundRequest {
+    pub version: u64,
+    pub route_amount: u64,
+    pub route_recipient: String,
+    pub dest_chain_id: String,
+    pub handler_address: String,
+    pub request_packet: Binary,
+    // hex encoded sha256 of the payload
+    pub payload_hash: String,
+    // packed metadata forwarded to the gateway
+    pub request_metadata: Binary,
+    pub caller: Addr,
+    pub height: u64,
+    pub time: Timestamp,
+    pub status: RequestStatus,
+    // id of the retry queue entry this request retries, None for the original request
+    pub retry_of: Option<u64>,
+    // failure of the original request that started the retries, None for the original request
+    pub original_request: Option<RetrySource>,
+    // 0 for the original request, n for its n-th retry
+    pub attempt: u32,
+}
+
+// why an outbound request landed in the retry queue
+#[cw_serde]
+pub enum RetrySource {
+    SendFailure { failed_request_id: u64 },
+    AckFailure { request_identifier: u64 },
+}
+
+#[cw_serde]
+pub struct RetryEntry {
+    pub request: OutboundRequest,
+    pub source: RetrySource,
+}
+
+// address format of a destination chain
+#[cw_serde]
+pub enum ChainType {
+    Evm,
+    Cosmos,
+}
+
+// defaults ISendTo uses for a destination chain
+#[cw_serde]
+pub struct ChainConfig {
+    pub chain_type: ChainType,
+    pub handler_address: String,
+    // packed metadata template
+    pub request_metadata: Binary,
+}
+
+// outcome of one ISendBatch entry, filled in by its ISend reply
+#[cw_serde]
+pub struct BatchEntryResult {
+    pub index: u32,
+    pub dest_chain_id: String,
+    // set when the gateway accepted the entry
+    pub request_identifier: Option<u64>,
+    // set when the gateway rejected the entry
+    pub failed_request_id: Option<u64>,
+}
+
+// inbound packet whose handler submessage is in flight
+#[cw_serde]
+pub struct PendingIReceive {
+    pub caller: Owner,
+    pub funds: Vec<Coin>,
+    pub payload: Binary,
+}
+
+#[cw_serde]
+pub struct PendingBatch {
+    // number of ISend replies still to come
+    pub remaining: u32,
+    pub allow_partial_failure: bool,
+    pub entries: Vec<BatchEntryResult>,
+}
+
+// outbound request the gateway rejected, recorded under ReplyFailurePolicy::Record
+#[cw_serde]
+pub struct FailedRequest {
+    pub request: OutboundRequest,
+    pub error: String,
+}
+
+// outcome of an outbound request, as reported by IAck
+#[cw_serde]
+pub struct AckResult {
+    pub exec_status: bool,
+    // exec_data with the abi encoding stripped
+    pub exec_data: Binary,
+}
+
+// fee payer configured on the gateway through SetDappMetadata
+#[cw_serde]
+pub struct DappMetadata {
+    pub fee_payer_address: String,
+    // data the gateway answered SetDappMetadata with, if any
+    pub gateway_response: Option<Binary>,
+}
+
+pub struct OutboundRequestIndexes<'a> {
+    pub status: MultiIndex<'a, String, OutboundRequest, u64>,
+    pub dest_chain_id: MultiIndex<'a, String, OutboundRequest, u64>,
+}
+
+impl<'a> IndexList<OutboundRequest> for OutboundRequestIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<OutboundRequest>> + '_> {
+        let v: Vec<&dyn Index<OutboundRequest>> = vec![&self.status, &self.dest_chain_id];
+        Box::new(v.into_iter())
+    }
+}
+
+// outbound requests keyed by the request_identifier the gateway returns from ISend
+pub fn outbound_requests<'a>() -> IndexedMap<'a, u64, OutboundRequest, OutboundRequestIndexes<'a>> {
+    let indexes = OutboundRequestIndexes {
+        status: MultiIndex::new(
+            |_pk, request| request.status.as_str().to_string(),
+            "outbound_requests",
+            "outbound_requests__status",
+        ),
+        dest_chain_id: MultiIndex::new(
+            |_pk, request| request.dest_chain_id.clone(),
+            "outbound_requests",
+            "outbound_requests__dest_chain_id",
+        ),
+    };
+    IndexedMap::new("outbound_requests", indexes)
+}
+
+pub struct NameRecordIndexes<'a> {
+    // (chain_id, address) of the owner
+    pub owner: MultiIndex<'a, (String, String), NameRecord, Vec<u8>>,
+}
+
+impl<'a> IndexList<NameRecord> for NameRecordIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<NameRecord>> + '_> {
+        let v: Vec<&dyn Index<NameRecord>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+// name records keyed by name, which is also the cw721 token id
+pub fn name_records<'a>() -> IndexedMap<'a, &'a [u8], NameRecord, NameRecordIndexes<'a>> {
+    let indexes = NameRecordIndexes {
+        owner: MultiIndex::new(
+            |_pk, record| (record.owner.chain_id.clone(), record.owner.address.clone()),
+            "name_resolver",
+            "name_resolver__owner",
+        ),
+    };
+    IndexedMap::new("name_resolver", indexes)
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+// removed when the admin role is renounced
+pub const ADMIN: Item<Addr> = Item::new("admin");
+// proposed admin, becomes ADMIN once it accepts
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+// inbound packets already executed, keyed by inbound_packet_key, valued by block height
+pub const PROCESSED_PACKETS: Map<&[u8], u64> = Map::new("processed_packets");
+// allowlist of remote handler contracts, keyed by (src_chain_id, request_sender)
+pub const TRUSTED_SENDERS: Map<(&str, &str), Empty> = Map::new("trusted_senders");
+// dest_chain_id -> chain config
+pub const CHAINS: Map<&str, ChainConfig> = Map::new("chains");
+
+// addresses a name resolves to, keyed by (name, chain_id)
+pub const NAME_TARGETS: Map<(&[u8], &str), String> = Map::new("name_targets");
+// cw721 spenders of a name, keyed by (name, spender)
+pub const APPROVALS: Map<(&[u8], &str), Expiration> = Map::new("approvals");
+// subdomains of a name, keyed by (parent, label)
+pub const SUBDOMAINS: Map<(&str, &str), Empty> = Map::new("subdomains");
+// primary name of an owner for reverse resolution, keyed by (chain_id, address)
+pub const PRIMARY_NAMES: Map<(&str, &str), String> = Map::new("primary_names");
+
+pub const AUCTIONS: Map<&[u8], Auction> = Map::new("auctions");
+// refunds owed to remote accounts, keyed by (chain_id, address)
+pub const CREDITS: Map<(&str, &str), Vec<Coin>> = Map::new("credits");
+
+pub const ACK_RESULTS: Map<u64, AckResult> = Map::new("ack_results");
+// failed requests have no request_identifier, they are keyed by a local sequence
+pub const FAILED_REQUESTS: Map<u64, FailedRequest> = Map::new("failed_requests");
+pub const FAILED_REQUEST_SEQ: Item<u64> = Item::new("failed_request_seq");
+pub const DAPP_METADATA: Item<DappMetadata> = Item::new("dapp_metadata");
+// fee payer being configured, it is moved to DAPP_METADATA once the gateway replies
+pub const PENDING_FEE_PAYER: Item<String> = Item::new("pending_fee_payer");
+// inbound packet being handled, removed once its handler submessage replies
+pub const PENDING_I_RECEIVE: Item<PendingIReceive> = Item::new("pending_i_receive");
+// outbound requests being sent, each is keyed by request_identifier once its ISend reply arrives
+pub const PENDING_SENDS: Deque<OutboundRequest> = Deque::new("pending_sends");
+// ISendBatch being sent, removed once its last reply arrives
+pub const PENDING_BATCH: Item<PendingBatch> = Item::new("pending_batch");
+pub const RETRY_QUEUE: Map<u64, RetryEntry> = Map::new("retry_queue");
+pub const RETRY_SEQ: Item<u64> = Item::new("retry_seq");
+
+pub const DEBUG_STATE: Item<DebugState> = Item::new("debug_state");
+pub const NONCE: Item<u64> = Item::new("nonce");
+
+// legacy layouts, only read by migrate
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const LEGACY_NAME_RESOLVER: Map<&[u8], LegacyNameRecord> = Map::new("name_resolver");
+pub const UNLEASED_NAME_RESOLVER: Map<&[u8], UnleasedNameRecord> = Map::new("name_resolver");
+pub const REQUEST: Item<Binary> = Item::new("request");
+pub const RESULT: Item<Binary> = Item::new("result");
+pub const PENDING: Item<PendingRequests> = Item::new("pending");
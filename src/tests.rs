@@ -1,390 +1,3242 @@
-// use cosmwasm_std::Binary;
-
-// use crate::helper::{abi_encode_string, myprint};
-
-#[cfg(test)]
-mod test_module {
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coin, coins, from_binary, Coin, Deps, DepsMut, Binary};
-
-    use crate::contract::{execute, instantiate, query};
-    use crate::error::ContractError;
-    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ResolveRecordResponse, ResolveResultResponse};
-    use crate::state::Config;
-
-    fn assert_name_owner(deps: Deps, name: &str, owner: &str) {
-        let res = query(
-            deps,
-            mock_env(),
-            QueryMsg::ResolveRecord {
-                name: name.to_string(),
-            },
-        )
-        .unwrap();
-
-        let value: ResolveRecordResponse = from_binary(&res).unwrap();
-        assert_eq!(Some(owner.to_string()), value.address);
-    }
-
-    fn assert_config_state(deps: Deps, expected: Config) {
-        let res = query(deps, mock_env(), QueryMsg::Config {}).unwrap();
-        let value: Config = from_binary(&res).unwrap();
-        assert_eq!(value, expected);
-    }
-
-    fn mock_init_with_price(deps: DepsMut, purchase_price: Coin, transfer_price: Coin) {
-        let msg = InstantiateMsg {
-            purchase_price: Some(purchase_price),
-            transfer_price: Some(transfer_price),
-        };
-
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps, mock_env(), info, msg)
-            .expect("contract successfully handles InstantiateMsg");
-    }
-
-    fn mock_init_no_price(deps: DepsMut) {
-        let msg = InstantiateMsg {
-            purchase_price: None,
-            transfer_price: None,
-        };
-
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps, mock_env(), info, msg)
-            .expect("contract successfully handles InstantiateMsg");
-    }
-
-    fn mock_alice_registers_name(deps: DepsMut, sent: &[Coin]) {
-        // alice can register an available name
-        let info = mock_info("alice_key", sent);
-        let msg = ExecuteMsg::Register {
-            name: "alice".to_string(),
-        };
-        let _res = execute(deps, mock_env(), info, msg)
-            .expect("contract successfully handles Register message");
-    }
-
-    #[test]
-    fn proper_init_no_fees() {
-        let mut deps = mock_dependencies();
-
-        mock_init_no_price(deps.as_mut());
-
-        assert_config_state(
-            deps.as_ref(),
-            Config {
-                purchase_price: None,
-                transfer_price: None,
-            },
-        );
-    }
-
-    #[test]
-    fn proper_init_with_fees() {
-        let mut deps = mock_dependencies();
-
-        mock_init_with_price(deps.as_mut(), coin(3, "token"), coin(4, "token"));
-
-        assert_config_state(
-            deps.as_ref(),
-            Config {
-                purchase_price: Some(coin(3, "token")),
-                transfer_price: Some(coin(4, "token")),
-            },
-        );
-    }
-
-    #[test]
-    fn register_available_name_and_query_works() {
-        let mut deps = mock_dependencies();
-        mock_init_no_price(deps.as_mut());
-        mock_alice_registers_name(deps.as_mut(), &[]);
-
-        // querying for name resolves to correct address
-        assert_name_owner(deps.as_ref(), "alice", "alice_key");
-    }
-
-    #[test]
-    fn register_available_name_and_query_works_with_fees() {
-        let mut deps = mock_dependencies();
-        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
-        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
-
-        // anyone can register an available name with more fees than needed
-        let info = mock_info("bob_key", &coins(5, "token"));
-        let msg = ExecuteMsg::Register {
-            name: "bob".to_string(),
-        };
-
-        let _res = execute(deps.as_mut(), mock_env(), info, msg)
-            .expect("contract successfully handles Register message");
-
-        // querying for name resolves to correct address
-        assert_name_owner(deps.as_ref(), "alice", "alice_key");
-        assert_name_owner(deps.as_ref(), "bob", "bob_key");
-    }
-
-    #[test]
-    fn fails_on_register_already_taken_name() {
-        let mut deps = mock_dependencies();
-        mock_init_no_price(deps.as_mut());
-        mock_alice_registers_name(deps.as_mut(), &[]);
-
-        // bob can't register the same name
-        let info = mock_info("bob_key", &coins(2, "token"));
-        let msg = ExecuteMsg::Register {
-            name: "alice".to_string(),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::NameTaken { .. }) => {}
-            Err(_) => panic!("Unknown error"),
-        }
-        // alice can't register the same name again
-        let info = mock_info("alice_key", &coins(2, "token"));
-        let msg = ExecuteMsg::Register {
-            name: "alice".to_string(),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::NameTaken { .. }) => {}
-            Err(e) => panic!("Unexpected error: {:?}", e),
-        }
-    }
-
-    #[test]
-    fn register_available_name_fails_with_invalid_name() {
-        let mut deps = mock_dependencies();
-        mock_init_no_price(deps.as_mut());
-        let info = mock_info("bob_key", &coins(2, "token"));
-
-        // hi is too short
-        let msg = ExecuteMsg::Register {
-            name: "hi".to_string(),
-        };
-        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::NameTooShort { .. }) => {}
-            Err(_) => panic!("Unknown error"),
-        }
-
-        // 65 chars is too long
-        let msg = ExecuteMsg::Register {
-            name: "01234567890123456789012345678901234567890123456789012345678901234".to_string(),
-        };
-        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::NameTooLong { .. }) => {}
-            Err(_) => panic!("Unknown error"),
-        }
-
-        // no upper case...
-        let msg = ExecuteMsg::Register {
-            name: "LOUD".to_string(),
-        };
-        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::InvalidCharacter { c }) => assert_eq!(c, 'L'),
-            Err(_) => panic!("Unknown error"),
-        }
-        // ... or spaces
-        let msg = ExecuteMsg::Register {
-            name: "two words".to_string(),
-        };
-        match execute(deps.as_mut(), mock_env(), info, msg) {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::InvalidCharacter { .. }) => {}
-            Err(_) => panic!("Unknown error"),
-        }
-    }
-
-    #[test]
-    fn fails_on_register_insufficient_fees() {
-        let mut deps = mock_dependencies();
-        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
-
-        // anyone can register an available name with sufficient fees
-        let info = mock_info("alice_key", &[]);
-        let msg = ExecuteMsg::Register {
-            name: "alice".to_string(),
-        };
-
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("register call should fail with insufficient fees"),
-            Err(ContractError::InsufficientFundsSend {}) => {}
-            Err(e) => panic!("Unexpected error: {:?}", e),
-        }
-    }
-
-    #[test]
-    fn fails_on_register_wrong_fee_denom() {
-        let mut deps = mock_dependencies();
-        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
-
-        // anyone can register an available name with sufficient fees
-        let info = mock_info("alice_key", &coins(2, "earth"));
-        let msg = ExecuteMsg::Register {
-            name: "alice".to_string(),
-        };
-
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("register call should fail with insufficient fees"),
-            Err(ContractError::InsufficientFundsSend {}) => {}
-            Err(e) => panic!("Unexpected error: {:?}", e),
-        }
-    }
-
-    #[test]
-    fn transfer_works() {
-        let mut deps = mock_dependencies();
-        mock_init_no_price(deps.as_mut());
-        mock_alice_registers_name(deps.as_mut(), &[]);
-
-        // alice can transfer her name successfully to bob
-        let info = mock_info("alice_key", &[]);
-        let msg = ExecuteMsg::Transfer {
-            name: "alice".to_string(),
-            to: "bob_key".to_string(),
-        };
-
-        let _res = execute(deps.as_mut(), mock_env(), info, msg)
-            .expect("contract successfully handles Transfer message");
-        // querying for name resolves to correct address (bob_key)
-        assert_name_owner(deps.as_ref(), "alice", "bob_key");
-    }
-
-    #[test]
-    fn transfer_works_with_fees() {
-        let mut deps = mock_dependencies();
-        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
-        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
-
-        // alice can transfer her name successfully to bob
-        let info = mock_info("alice_key", &[coin(1, "earth"), coin(2, "token")]);
-        let msg = ExecuteMsg::Transfer {
-            name: "alice".to_string(),
-            to: "bob_key".to_string(),
-        };
-
-        let _res = execute(deps.as_mut(), mock_env(), info, msg)
-            .expect("contract successfully handles Transfer message");
-        // querying for name resolves to correct address (bob_key)
-        assert_name_owner(deps.as_ref(), "alice", "bob_key");
-    }
-
-    #[test]
-    fn fails_on_transfer_non_existent() {
-        let mut deps = mock_dependencies();
-        mock_init_no_price(deps.as_mut());
-        mock_alice_registers_name(deps.as_mut(), &[]);
-
-        // alice can transfer her name successfully to bob
-        let info = mock_info("frank_key", &coins(2, "token"));
-        let msg = ExecuteMsg::Transfer {
-            name: "alice42".to_string(),
-            to: "bob_key".to_string(),
-        };
-
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::NameNotExists { name }) => assert_eq!(name, "alice42"),
-            Err(e) => panic!("Unexpected error: {:?}", e),
-        }
-
-        // querying for name resolves to correct address (alice_key)
-        assert_name_owner(deps.as_ref(), "alice", "alice_key");
-    }
-
-    #[test]
-    fn fails_on_transfer_from_nonowner() {
-        let mut deps = mock_dependencies();
-        mock_init_no_price(deps.as_mut());
-        mock_alice_registers_name(deps.as_mut(), &[]);
-
-        // alice can transfer her name successfully to bob
-        let info = mock_info("frank_key", &coins(2, "token"));
-        let msg = ExecuteMsg::Transfer {
-            name: "alice".to_string(),
-            to: "bob_key".to_string(),
-        };
-
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("Must return error"),
-            Err(ContractError::Unauthorized { .. }) => {}
-            Err(e) => panic!("Unexpected error: {:?}", e),
-        }
-
-        // querying for name resolves to correct address (alice_key)
-        assert_name_owner(deps.as_ref(), "alice", "alice_key");
-    }
-
-    #[test]
-    fn fails_on_transfer_insufficient_fees() {
-        let mut deps = mock_dependencies();
-        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(5, "token"));
-        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
-
-        // alice can transfer her name successfully to bob
-        let info = mock_info("alice_key", &[coin(1, "earth"), coin(2, "token")]);
-        let msg = ExecuteMsg::Transfer {
-            name: "alice".to_string(),
-            to: "bob_key".to_string(),
-        };
-
-        let res = execute(deps.as_mut(), mock_env(), info, msg);
-
-        match res {
-            Ok(_) => panic!("register call should fail with insufficient fees"),
-            Err(ContractError::InsufficientFundsSend {}) => {}
-            Err(e) => panic!("Unexpected error: {:?}", e),
-        }
-
-        // querying for name resolves to correct address (bob_key)
-        assert_name_owner(deps.as_ref(), "alice", "alice_key");
-    }
-
-    #[test]
-    fn returns_empty_on_query_unregistered_name() {
-        let mut deps = mock_dependencies();
-
-        mock_init_no_price(deps.as_mut());
-
-        // querying for unregistered name results in NotFound error
-        let res = query(
-            deps.as_ref(),
-            mock_env(),
-            QueryMsg::ResolveRecord {
-                name: "alice".to_string(),
-            },
-        )
-        .unwrap();
-        let value: ResolveRecordResponse = from_binary(&res).unwrap();
-        assert_eq!(None, value.address);
-    }
-}   
-
-
-// #[test]
-// fn test_i_ack_result() {
-//     let contract_address = "osmo18f0822ahrpl3xzvuxwjfl0qz53z8cn3ncaa2fr2zf0xc2rzsytcq77k0zq".to_string();
-//     let request_identifier: u64 = 2;
-//     let exec_status = true;
-//     let exec_data = Binary::from_base64("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAACAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAJXsicmVzb2x2ZV9yZWNvcmQiOiB7Im5hbWUiOiAidGVzdDUifX0AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=").unwrap();
-//     let result_txt = format!("Ack from handler contract, \naddress:{}, \nrequest_identifier: {}, \nexec_status:{}, \nexec_data:{:?}", 
-//         contract_address, request_identifier, exec_status, exec_data);
-//     // let result_txt = "result_txt".to_string();
-//     let result = abi_encode_string(&result_txt);
-//     myprint(format!("result-txt: {}\nresult: {:?}\nbase64: {}", result_txt, result, result.to_base64()));
+// use cosmwasm_std::Binary;
+
+// use crate::helper::{abi_encode_string, myprint};
+
+#[cfg(test)]
+mod test_module {
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut,
+        Env, MessageInfo, Order, OwnedDeps, Reply, Response, StdError, Storage, SubMsgResponse,
+        SubMsgResult, Uint128, WasmMsg,
+    };
+    use cw2::{get_contract_version, set_contract_version};
+    use cw_storage_plus::Map;
+    use cw_utils::Expiration;
+    use router_wasm_bindings::ethabi::{encode, Token};
+
+    use crate::consts::{
+        AUCTION_DURATION_SECONDS, CONTRACT_NAME, CONTRACT_VERSION, DEFAULT_MAX_RETRY_ATTEMPTS,
+        GRACE_PERIOD_SECONDS, ISEND_ID, I_RECEIVE_ID, SECONDS_PER_YEAR, SET_DAPP_METADATA_ID,
+    };
+    use crate::contract::{execute, instantiate, query};
+    use crate::error::ContractError;
+    use crate::helper::{
+        abi_decode_ack, abi_decode_config, abi_decode_resolve_record, get_request_metadata,
+    };
+    use crate::msg::{
+        AdminResponse, AuctionResponse, ChainResponse, ChainsResponse, CreditsResponse,
+        CustomExecuteMsg, Cw721ReceiveMsg, DappMetadataResponse, ExecuteMsg, FailedRequestsResponse,
+        GatewayMsg, ISendBatchResponse, ISendRequest, InstantiateMsg, IsTrustedSenderResponse,
+        LegacyMigrateMsg, LoadStatesResponse, MigrateMsg, NftInfoResponse, OutboundRequestResponse,
+        OutboundRequestsResponse, OwnerOfResponse, PacketProcessedResponse, QueryMsg,
+        ReceiverExecuteMsg, RequestMetadata, ResolveRecordResponse, ResolveResultResponse,
+        RetryEntryResponse, RetryQueueResponse, ReverseResolveResponse, SubdomainsResponse,
+        TokensResponse, TrustedSendersResponse,
+    };
+    use crate::migrate::migrate;
+    use crate::reply::reply;
+    use crate::state::{
+        Auction, ChainType, Config, LegacyConfig, LegacyNameRecord, NameRecord, Owner,
+        PendingRequests, ReplyFailurePolicy, RequestStatus, RetrySource, UnleasedNameRecord,
+        AUCTIONS, LEGACY_CONFIG, LEGACY_NAME_RESOLVER, PENDING, REQUEST, RESULT,
+        UNLEASED_NAME_RESOLVER,
+    };
+
+    const GATEWAY: &str = "router_gateway";
+
+    type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn assert_name_owner(deps: Deps, name: &str, owner: &str) {
+        let res = query(
+            deps,
+            mock_env(),
+            QueryMsg::ResolveRecord {
+                name: name.to_string(),
+                chain_id: None,
+            },
+        )
+        .unwrap();
+
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(owner.to_string()), value.address);
+    }
+
+    fn assert_config_state(deps: Deps, expected: Config) {
+        let res = query(deps, mock_env(), QueryMsg::Config {}).unwrap();
+        let value: Config = from_binary(&res).unwrap();
+        assert_eq!(value, expected);
+    }
+
+    fn mock_init_with_price(deps: DepsMut, purchase_price: Coin, transfer_price: Coin) {
+        let msg = InstantiateMsg {
+            purchase_price: Some(purchase_price),
+            transfer_price: Some(transfer_price),
+            gateway_address: GATEWAY.to_string(),
+            reply_failure_policy: None,
+            max_retry_attempts: None,
+            admin: None,
+        };
+
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps, mock_env(), info, msg)
+            .expect("contract successfully handles InstantiateMsg");
+    }
+
+    fn mock_init_no_price(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            purchase_price: None,
+            transfer_price: None,
+            gateway_address: GATEWAY.to_string(),
+            reply_failure_policy: None,
+            max_retry_attempts: None,
+            admin: None,
+        };
+
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps, mock_env(), info, msg)
+            .expect("contract successfully handles InstantiateMsg");
+    }
+
+    // abi encodes a custom msg the way a remote handler contract builds its packet
+    fn mock_packet(msg: &CustomExecuteMsg) -> Binary {
+        let json = to_binary(msg).unwrap();
+        Binary::from(encode(&[Token::Bytes(json.to_vec())]))
+    }
+
+    // runs an IReceive the way the chain does: the handler submessage is executed by
+    // the contract itself, its writes are dropped when it fails, then the reply builds the ack
+    fn mock_i_receive(
+        deps: &mut MockDeps,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> Result<Response, ContractError> {
+        let res = execute(deps.as_mut(), mock_env(), info, msg)?;
+        let (handle_msg, funds) = match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, funds, .. }) => (msg.clone(), funds.clone()),
+            msg => panic!("Unexpected message: {:?}", msg),
+        };
+        let handle_msg: ExecuteMsg = from_binary(&handle_msg).unwrap();
+
+        let snapshot: Vec<_> = deps.storage.range(None, None, Order::Ascending).collect();
+        let info = mock_info(mock_env().contract.address.as_str(), &funds);
+        let (result, handled) = match execute(deps.as_mut(), mock_env(), info, handle_msg) {
+            Ok(handled) => {
+                // wasm execute data reaches the reply wrapped in a protobuf message
+                let data = handled.data.clone().map(|data| {
+                    let mut wrapped = vec![0x0a];
+                    let mut len = data.len();
+                    while len >= 0x80 {
+                        wrapped.push((len as u8 & 0x7f) | 0x80);
+                        len >>= 7;
+                    }
+                    wrapped.push(len as u8);
+                    wrapped.extend_from_slice(data.as_slice());
+                    Binary::from(wrapped)
+                });
+                let result = SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data,
+                });
+                (result, handled)
+            }
+            Err(err) => {
+                let keys: Vec<_> = deps
+                    .storage
+                    .range(None, None, Order::Ascending)
+                    .map(|(key, _)| key)
+                    .collect();
+                for key in keys {
+                    deps.storage.remove(&key);
+                }
+                for (key, value) in snapshot {
+                    deps.storage.set(&key, &value);
+                }
+                (SubMsgResult::Err(err.to_string()), Response::new())
+            }
+        };
+
+        let reply_msg = Reply {
+            id: I_RECEIVE_ID,
+            result,
+        };
+        let res = reply(deps.as_mut(), mock_env(), reply_msg)?;
+        Ok(res.add_submessages(handled.messages).add_events(handled.events))
+    }
+
+    fn mock_trust_sender(deps: DepsMut, chain_id: &str, sender: &str) {
+        let msg = ExecuteMsg::AddTrustedSender {
+            chain_id: chain_id.to_string(),
+            sender: sender.to_string(),
+        };
+        let _res = execute(deps, mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles AddTrustedSender message");
+    }
+
+    fn mock_i_send(deps: DepsMut, dest_chain_id: &str) {
+        let msg = ExecuteMsg::ISend {
+            version: 1,
+            route_amount: 0,
+            route_recipient: "".to_string(),
+            dest_chain_id: dest_chain_id.to_string(),
+            request_metadata: Some(Binary::from(vec![0u8; 50])),
+            metadata: None,
+            handler_address: "0xhandler".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+        };
+        let _res = execute(deps, mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ISend message");
+    }
+
+    // the gateway answers ISend with the request_identifier wrapped in a MsgExecuteContractResponse
+    fn mock_i_send_reply(deps: DepsMut, request_identifier: u64) {
+        let identifier = to_binary(&request_identifier).unwrap();
+        let mut data = vec![0x0a, identifier.len() as u8];
+        data.extend(identifier.as_slice());
+        let msg = Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(data)),
+            }),
+        };
+        let _res = reply(deps, mock_env(), msg).expect("contract successfully handles ISend reply");
+    }
+
+    fn mock_i_ack(
+        deps: DepsMut,
+        request_identifier: u64,
+        exec_status: bool,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::IAck {
+            request_identifier,
+            exec_status,
+            exec_data: Binary::from(encode(&[Token::Bytes(b"done".to_vec())])),
+        };
+        execute(deps, mock_env(), mock_info(GATEWAY, &[]), msg)
+    }
+
+    fn mock_i_send_request(dest_chain_id: &str) -> ISendRequest {
+        ISendRequest {
+            version: 1,
+            route_amount: 0,
+            route_recipient: "".to_string(),
+            dest_chain_id: dest_chain_id.to_string(),
+            request_metadata: Some(Binary::from(vec![0u8; 50])),
+            metadata: None,
+            handler_address: "0xhandler".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+        }
+    }
+
+    fn mock_metadata(gas_limit: u64) -> RequestMetadata {
+        RequestMetadata {
+            gas_limit,
+            gas_price: 10,
+            ack_gas_limit: 0,
+            ack_gas_price: 0,
+            relayer_fees: Uint128::zero(),
+            ack_type: 0,
+            is_read_call: false,
+            asm_address: "".to_string(),
+        }
+    }
+
+    fn mock_set_chain(deps: DepsMut, dest_chain_id: &str, handler_address: &str) {
+        let msg = ExecuteMsg::SetChain {
+            dest_chain_id: dest_chain_id.to_string(),
+            chain_type: ChainType::Evm,
+            handler_address: handler_address.to_string(),
+            metadata: mock_metadata(300_000),
+        };
+        let _res = execute(deps, mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles SetChain message");
+    }
+
+    fn mock_env_after(seconds: u64) -> Env {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env
+    }
+
+    fn mock_alice_registers_name(deps: DepsMut, sent: &[Coin]) {
+        // alice can register an available name
+        let info = mock_info("alice_key", sent);
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        let _res = execute(deps, mock_env(), info, msg)
+            .expect("contract successfully handles Register message");
+    }
+
+    #[test]
+    fn proper_init_no_fees() {
+        let mut deps = mock_dependencies();
+
+        mock_init_no_price(deps.as_mut());
+
+        assert_config_state(
+            deps.as_ref(),
+            Config {
+                purchase_price: None,
+                transfer_price: None,
+                gateway_address: Addr::unchecked(GATEWAY),
+                reply_failure_policy: ReplyFailurePolicy::Strict,
+                max_retry_attempts: DEFAULT_MAX_RETRY_ATTEMPTS,
+            },
+        );
+    }
+
+    #[test]
+    fn proper_init_with_fees() {
+        let mut deps = mock_dependencies();
+
+        mock_init_with_price(deps.as_mut(), coin(3, "token"), coin(4, "token"));
+
+        assert_config_state(
+            deps.as_ref(),
+            Config {
+                purchase_price: Some(coin(3, "token")),
+                transfer_price: Some(coin(4, "token")),
+                gateway_address: Addr::unchecked(GATEWAY),
+                reply_failure_policy: ReplyFailurePolicy::Strict,
+                max_retry_attempts: DEFAULT_MAX_RETRY_ATTEMPTS,
+            },
+        );
+    }
+
+    #[test]
+    fn register_available_name_and_query_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // querying for name resolves to correct address
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+    }
+
+    #[test]
+    fn register_available_name_and_query_works_with_fees() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
+
+        // anyone can register an available name with more fees than needed
+        let info = mock_info("bob_key", &coins(5, "token"));
+        let msg = ExecuteMsg::Register {
+            name: "bob".to_string(),
+            years: None,
+        };
+
+        let _res = execute(deps.as_mut(), mock_env(), info, msg)
+            .expect("contract successfully handles Register message");
+
+        // querying for name resolves to correct address
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+        assert_name_owner(deps.as_ref(), "bob", "bob_key");
+    }
+
+    #[test]
+    fn fails_on_register_already_taken_name() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // bob can't register the same name
+        let info = mock_info("bob_key", &coins(2, "token"));
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTaken { .. }) => {}
+            Err(_) => panic!("Unknown error"),
+        }
+        // alice can't register the same name again
+        let info = mock_info("alice_key", &coins(2, "token"));
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTaken { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn register_available_name_fails_with_invalid_name() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        let info = mock_info("bob_key", &coins(2, "token"));
+
+        // hi is too short
+        let msg = ExecuteMsg::Register {
+            name: "hi".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTooShort { .. }) => {}
+            Err(_) => panic!("Unknown error"),
+        }
+
+        // 65 chars is too long
+        let msg = ExecuteMsg::Register {
+            name: "01234567890123456789012345678901234567890123456789012345678901234".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTooLong { .. }) => {}
+            Err(_) => panic!("Unknown error"),
+        }
+
+        // no upper case...
+        let msg = ExecuteMsg::Register {
+            name: "LOUD".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidCharacter { c }) => assert_eq!(c, 'L'),
+            Err(_) => panic!("Unknown error"),
+        }
+        // ... or spaces
+        let msg = ExecuteMsg::Register {
+            name: "two words".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidCharacter { .. }) => {}
+            Err(_) => panic!("Unknown error"),
+        }
+    }
+
+    #[test]
+    fn fails_on_register_insufficient_fees() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+
+        // anyone can register an available name with sufficient fees
+        let info = mock_info("alice_key", &[]);
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("register call should fail with insufficient fees"),
+            Err(ContractError::InsufficientFundsSend {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_register_wrong_fee_denom() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+
+        // anyone can register an available name with sufficient fees
+        let info = mock_info("alice_key", &coins(2, "earth"));
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("register call should fail with insufficient fees"),
+            Err(ContractError::InsufficientFundsSend {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn expired_names_are_released_after_grace_period() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // an expired name no longer resolves
+        let msg = QueryMsg::ResolveRecord {
+            name: "alice".to_string(),
+            chain_id: None,
+        };
+        let res = query(deps.as_ref(), mock_env_after(SECONDS_PER_YEAR), msg).unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(value.address, None);
+
+        // nor can it be transferred
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+        let env = mock_env_after(SECONDS_PER_YEAR);
+        match execute(deps.as_mut(), env, mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameExpired { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // it stays reserved during the grace period
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        let env = mock_env_after(SECONDS_PER_YEAR + GRACE_PERIOD_SECONDS - 1);
+        match execute(deps.as_mut(), env, mock_info("bob_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTaken { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // and is up for grabs afterwards
+        let env = mock_env_after(SECONDS_PER_YEAR + GRACE_PERIOD_SECONDS);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("bob_key", &[]), msg)
+            .expect("contract successfully handles Register message");
+        let msg = QueryMsg::ResolveRecord {
+            name: "alice".to_string(),
+            chain_id: None,
+        };
+        let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(value.address, Some("bob_key".to_string()));
+        assert_eq!(value.expires, Some(env.block.time.plus_seconds(SECONDS_PER_YEAR)));
+    }
+
+    #[test]
+    fn renew_works() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+
+        // registration is priced per year
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: Some(2),
+        };
+        let info = mock_info("alice_key", &coins(2, "token"));
+        match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InsufficientFundsSend {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let info = mock_info("alice_key", &coins(4, "token"));
+        let _res = execute(deps.as_mut(), mock_env(), info, msg)
+            .expect("contract successfully handles Register message");
+
+        let msg = ExecuteMsg::Renew {
+            name: "alice".to_string(),
+            years: 0,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &coins(2, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidYears { years, .. }) => assert_eq!(years, 0),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // renewing during the grace period extends from the old expiry
+        let env = mock_env_after(2 * SECONDS_PER_YEAR + 1);
+        let msg = ExecuteMsg::Renew {
+            name: "alice".to_string(),
+            years: 1,
+        };
+        let info = mock_info("bob_key", &coins(2, "token"));
+        let _res = execute(deps.as_mut(), env.clone(), info, msg)
+            .expect("contract successfully handles Renew message");
+        let msg = QueryMsg::ResolveRecord {
+            name: "alice".to_string(),
+            chain_id: None,
+        };
+        let res = query(deps.as_ref(), env, msg).unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(value.address, Some("alice_key".to_string()));
+        assert_eq!(value.expires, Some(mock_env_after(3 * SECONDS_PER_YEAR).block.time));
+
+        // names released after the grace period can't be renewed
+        let env = mock_env_after(3 * SECONDS_PER_YEAR + GRACE_PERIOD_SECONDS);
+        let msg = ExecuteMsg::Renew {
+            name: "alice".to_string(),
+            years: 1,
+        };
+        match execute(deps.as_mut(), env, mock_info("alice_key", &coins(2, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameExpired { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_lease_price_overflow() {
+        let mut deps = mock_dependencies();
+        let price = coin(u128::MAX / 2, "token");
+        mock_init_with_price(deps.as_mut(), price.clone(), price);
+
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: Some(3),
+        };
+        let info = mock_info("alice_key", &coins(u128::MAX, "token"));
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Std(StdError::Overflow { .. })) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_transfer_to_unknown_remote_owner() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // a name can't go to a chain this contract doesn't know
+        let transfer = |to: &str, to_chain_id: &str| ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: to.to_string(),
+            to_chain_id: Some(to_chain_id.to_string()),
+        };
+        let owner = "0xabcdef0123456789abcdef0123456789abcdef01";
+        let msg = transfer(owner, "80001");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::ChainNotRegistered { dest_chain_id }) => {
+                assert_eq!(dest_chain_id, "80001")
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // nor to an address that doesn't fit the chain
+        mock_set_chain(deps.as_mut(), "80001", owner);
+        let msg = transfer("0xabcdef", "80001");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidAddress { address }) => assert_eq!(address, "0xabcdef"),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+
+        let msg = transfer(owner, "80001");
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles Transfer message");
+        assert_name_owner(deps.as_ref(), "alice", owner);
+    }
+
+    #[test]
+    fn transfer_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // alice can transfer her name successfully to bob
+        let info = mock_info("alice_key", &[]);
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+
+        let _res = execute(deps.as_mut(), mock_env(), info, msg)
+            .expect("contract successfully handles Transfer message");
+        // querying for name resolves to correct address (bob_key)
+        assert_name_owner(deps.as_ref(), "alice", "bob_key");
+    }
+
+    #[test]
+    fn transfer_works_with_fees() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
+
+        // alice can transfer her name successfully to bob
+        let info = mock_info("alice_key", &[coin(1, "earth"), coin(2, "token")]);
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+
+        let _res = execute(deps.as_mut(), mock_env(), info, msg)
+            .expect("contract successfully handles Transfer message");
+        // querying for name resolves to correct address (bob_key)
+        assert_name_owner(deps.as_ref(), "alice", "bob_key");
+    }
+
+    #[test]
+    fn fails_on_transfer_non_existent() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // alice can transfer her name successfully to bob
+        let info = mock_info("frank_key", &coins(2, "token"));
+        let msg = ExecuteMsg::Transfer {
+            name: "alice42".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameNotExists { name }) => assert_eq!(name, "alice42"),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // querying for name resolves to correct address (alice_key)
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+    }
+
+    #[test]
+    fn fails_on_transfer_from_nonowner() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // alice can transfer her name successfully to bob
+        let info = mock_info("frank_key", &coins(2, "token"));
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // querying for name resolves to correct address (alice_key)
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+    }
+
+    #[test]
+    fn fails_on_transfer_insufficient_fees() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(5, "token"));
+        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
+
+        // alice can transfer her name successfully to bob
+        let info = mock_info("alice_key", &[coin(1, "earth"), coin(2, "token")]);
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+        match res {
+            Ok(_) => panic!("register call should fail with insufficient fees"),
+            Err(ContractError::InsufficientFundsSend {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // querying for name resolves to correct address (bob_key)
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+    }
+
+    #[test]
+    fn i_receive_from_gateway_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+
+        let info = mock_info(GATEWAY, &[]);
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Register {
+                name: "remote".to_string(),
+                years: None,
+            }),
+            request_identifier: None,
+        };
+
+        let _res = mock_i_receive(&mut deps, info, msg)
+            .expect("contract successfully handles IReceive message");
+        // the remote request sender owns the name, not the gateway
+        assert_name_owner(deps.as_ref(), "remote", "0xsender");
+    }
+
+    #[test]
+    fn fails_on_i_receive_from_non_gateway() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let info = mock_info("frank_key", &[]);
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Register {
+                name: "forged".to_string(),
+                years: None,
+            }),
+            request_identifier: None,
+        };
+
+        match mock_i_receive(&mut deps, info, msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::UnauthorizedGateway { sender }) => assert_eq!(sender, "frank_key"),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_i_ack_from_non_gateway() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let info = mock_info("frank_key", &[]);
+        let msg = ExecuteMsg::IAck {
+            request_identifier: 1,
+            exec_status: true,
+            exec_data: Binary::from(encode(&[Token::Bytes(vec![])])),
+        };
+
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::UnauthorizedGateway { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn update_gateway_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // only the admin can rotate the gateway
+        let msg = ExecuteMsg::UpdateGateway {
+            gateway_address: "new_gateway".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles UpdateGateway message");
+        assert_config_state(
+            deps.as_ref(),
+            Config {
+                purchase_price: None,
+                transfer_price: None,
+                gateway_address: Addr::unchecked("new_gateway"),
+                reply_failure_policy: ReplyFailurePolicy::Strict,
+                max_retry_attempts: DEFAULT_MAX_RETRY_ATTEMPTS,
+            },
+        );
+
+        // the old gateway is no longer trusted
+        let msg = ExecuteMsg::IAck {
+            request_identifier: 1,
+            exec_status: true,
+            exec_data: Binary::from(encode(&[Token::Bytes(vec![])])),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info(GATEWAY, &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::UnauthorizedGateway { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // outgoing messages go to the new gateway
+        let msg = ExecuteMsg::ISend {
+            version: 1,
+            route_amount: 0,
+            route_recipient: "".to_string(),
+            dest_chain_id: "80001".to_string(),
+            request_metadata: Some(Binary::from(vec![0u8; 50])),
+            metadata: None,
+            handler_address: "0xhandler".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ISend message");
+        let msg = ExecuteMsg::SetDappMetadata {
+            fee_payer_address: "fee_payer".to_string(),
+        };
+        let res2 = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles SetDappMetadata message");
+        for submsg in res.messages.iter().chain(res2.messages.iter()) {
+            match &submsg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => {
+                    assert_eq!(contract_addr, "new_gateway")
+                }
+                other => panic!("Unexpected msg: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn update_config_works() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+
+        // only the admin can change prices
+        let msg = ExecuteMsg::UpdateConfig {
+            purchase_price: Some(coin(5, "uroute")),
+            transfer_price: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles UpdateConfig message");
+        let attributes: Vec<(&str, &str)> = res.events[0]
+            .attributes
+            .iter()
+            .map(|attr| (attr.key.as_str(), attr.value.as_str()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("old_purchase_price", "2token"),
+                ("new_purchase_price", "5uroute"),
+                ("old_transfer_price", "2token"),
+                ("new_transfer_price", "none"),
+            ]
+        );
+        assert_config_state(
+            deps.as_ref(),
+            Config {
+                purchase_price: Some(coin(5, "uroute")),
+                transfer_price: None,
+                gateway_address: Addr::unchecked(GATEWAY),
+                reply_failure_policy: ReplyFailurePolicy::Strict,
+                max_retry_attempts: DEFAULT_MAX_RETRY_ATTEMPTS,
+            },
+        );
+
+        // the new denom is charged
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        let info = mock_info("alice_key", &coins(5, "token"));
+        match execute(deps.as_mut(), mock_env(), info, msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InsufficientFundsSend {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let info = mock_info("alice_key", &coins(5, "uroute"));
+        let _res = execute(deps.as_mut(), mock_env(), info, msg)
+            .expect("contract successfully handles Register message");
+
+        // denoms are validated
+        for denom in ["1token", "ab", "to ken"] {
+            let msg = ExecuteMsg::UpdateConfig {
+                purchase_price: None,
+                transfer_price: Some(coin(1, denom)),
+            };
+            match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg) {
+                Ok(_) => panic!("Must return error"),
+                Err(ContractError::InvalidDenom { denom: got }) => assert_eq!(got, denom),
+                Err(e) => panic!("Unexpected error: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn admin_transfer_works() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            purchase_price: None,
+            transfer_price: None,
+            gateway_address: GATEWAY.to_string(),
+            reply_failure_policy: None,
+            max_retry_attempts: None,
+            admin: Some("admin_key".to_string()),
+        };
+        let _res = instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles InstantiateMsg");
+
+        // only the admin can propose
+        let msg = ExecuteMsg::ProposeAdmin {
+            admin: "bob_key".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("admin_key", &[]), msg)
+            .expect("contract successfully handles ProposeAdmin message");
+
+        // only the proposed admin can accept
+        let msg = ExecuteMsg::AcceptAdmin {};
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotPendingAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin {}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(value.admin, Some("admin_key".to_string()));
+        assert_eq!(value.pending_admin, Some("bob_key".to_string()));
+
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg)
+            .expect("contract successfully handles AcceptAdmin message");
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin {}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(value.admin, Some("bob_key".to_string()));
+        assert_eq!(value.pending_admin, None);
+
+        // the old admin lost its rights
+        let msg = ExecuteMsg::UpdateGateway {
+            gateway_address: "new_gateway".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("admin_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn renounce_admin_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let msg = ExecuteMsg::ProposeAdmin {
+            admin: "bob_key".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ProposeAdmin message");
+        let msg = ExecuteMsg::RenounceAdmin {};
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles RenounceAdmin message");
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Admin {}).unwrap();
+        let value: AdminResponse = from_binary(&res).unwrap();
+        assert_eq!(value.admin, None);
+        assert_eq!(value.pending_admin, None);
+
+        // the pending proposal is dropped and privileged messages are disabled
+        let msg = ExecuteMsg::AcceptAdmin {};
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotPendingAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = ExecuteMsg::SetReplyFailurePolicy {
+            policy: ReplyFailurePolicy::Record,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn remote_and_local_owners_coexist() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xSender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Register {
+                name: "remote".to_string(),
+                years: None,
+            }),
+            request_identifier: None,
+        };
+        let _res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+            .expect("contract successfully handles IReceive message");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ResolveRecord {
+                name: "remote".to_string(),
+                chain_id: None,
+            },
+        )
+        .unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(Some("0xsender".to_string()), value.address);
+        assert_eq!(Some("80001".to_string()), value.chain_id);
+
+        // a local account can't transfer the remotely owned name
+        let msg = ExecuteMsg::Transfer {
+            name: "remote".to_string(),
+            to: "alice_key".to_string(),
+            to_chain_id: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("0xsender", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // and the remote sender can't transfer the locally owned name
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Transfer {
+                name: "alice".to_string(),
+                to: "frank_key".to_string(),
+                to_chain_id: Some(mock_env().block.chain_id),
+            }),
+            request_identifier: None,
+        };
+        let res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+            .expect("contract acks handler errors");
+        let (success, error_code, _) = abi_decode_ack(&res.data.unwrap()).unwrap();
+        assert!(!success);
+        assert_eq!(error_code, ContractError::Unauthorized {}.code());
+
+        // the remote owner can hand its name over to a local account
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Transfer {
+                name: "remote".to_string(),
+                to: "bob_key".to_string(),
+                to_chain_id: Some(mock_env().block.chain_id),
+            }),
+            request_identifier: None,
+        };
+        let _res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+            .expect("contract successfully handles IReceive message");
+        assert_name_owner(deps.as_ref(), "remote", "bob_key");
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+    }
+
+    #[test]
+    fn i_receive_acks_handler_errors() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        let i_receive = |deps: &mut MockDeps, msg: &CustomExecuteMsg| {
+            let msg = ExecuteMsg::IReceive {
+                src_chain_id: "80001".to_string(),
+                request_sender: "0xsender".to_string(),
+                packet: mock_packet(msg),
+                request_identifier: None,
+            };
+            let res = mock_i_receive(deps, mock_info(GATEWAY, &[]), msg)
+                .expect("contract acks handler errors");
+            abi_decode_ack(&res.data.unwrap()).unwrap()
+        };
+
+        let (success, error_code, message) = i_receive(
+            &mut deps,
+            &CustomExecuteMsg::Register {
+                name: "alice".to_string(),
+                years: None,
+            },
+        );
+        assert!(!success);
+        assert_eq!(error_code, 10);
+        assert_eq!(message, "Name has been taken (name alice)");
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+
+        let (success, error_code, _) = i_receive(
+            &mut deps,
+            &CustomExecuteMsg::Register {
+                name: "hi".to_string(),
+                years: None,
+            },
+        );
+        assert!(!success);
+        assert_eq!(error_code, 11);
+
+        let (success, error_code, message) = i_receive(
+            &mut deps,
+            &CustomExecuteMsg::Register {
+                name: "remote".to_string(),
+                years: None,
+            },
+        );
+        assert!(success);
+        assert_eq!(error_code, 0);
+        assert!(message.contains("remote"));
+    }
+
+    #[test]
+    fn fails_on_handle_i_receive_from_other_sender() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // only the contract runs packet handlers, so no one can skip the gateway checks
+        let msg = ExecuteMsg::HandleIReceive {
+            caller: Owner {
+                chain_id: "80001".to_string(),
+                address: "0xsender".to_string(),
+            },
+            payload: mock_packet(&CustomExecuteMsg::Register {
+                name: "forged".to_string(),
+                years: None,
+            }),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_i_receive_replayed_packet() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+
+        let packet = mock_packet(&CustomExecuteMsg::Register {
+            name: "remote".to_string(),
+            years: None,
+        });
+        let processed = |deps: Deps, request_identifier: Option<u64>| {
+            let res = query(
+                deps,
+                mock_env(),
+                QueryMsg::PacketProcessed {
+                    src_chain_id: "80001".to_string(),
+                    request_sender: "0xsender".to_string(),
+                    packet: packet.clone(),
+                    request_identifier,
+                },
+            )
+            .unwrap();
+            from_binary::<PacketProcessedResponse>(&res).unwrap()
+        };
+        assert!(!processed(deps.as_ref(), None).processed);
+
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: packet.clone(),
+            request_identifier: None,
+        };
+        let _res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg.clone())
+            .expect("contract successfully handles IReceive message");
+
+        let value = processed(deps.as_ref(), None);
+        assert!(value.processed);
+        assert_eq!(value.height, Some(mock_env().block.height));
+        // a nonce identifies a different packet
+        assert!(!processed(deps.as_ref(), Some(1)).processed);
+
+        match execute(deps.as_mut(), mock_env(), mock_info(GATEWAY, &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::PacketAlreadyProcessed {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // identical content with distinct nonces are distinct packets
+        let transfer = mock_packet(&CustomExecuteMsg::Transfer {
+            name: "remote".to_string(),
+            to: "0xsender".to_string(),
+            to_chain_id: None,
+        });
+        for nonce in [1, 2] {
+            let msg = ExecuteMsg::IReceive {
+                src_chain_id: "80001".to_string(),
+                request_sender: "0xsender".to_string(),
+                packet: transfer.clone(),
+                request_identifier: Some(nonce),
+            };
+            let _res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+                .expect("contract successfully handles IReceive message");
+        }
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: transfer,
+            request_identifier: Some(2),
+        };
+        match mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::PacketAlreadyProcessed {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_i_receive_from_untrusted_sender() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+
+        // the same sender is not trusted on another chain
+        let info = mock_info(GATEWAY, &[]);
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "43113".to_string(),
+            request_sender: "0xsender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Register {
+                name: "remote".to_string(),
+                years: None,
+            }),
+            request_identifier: None,
+        };
+
+        match mock_i_receive(&mut deps, info, msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::UntrustedSender { chain_id, sender }) => {
+                assert_eq!(chain_id, "43113");
+                assert_eq!(sender, "0xsender");
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn trusted_senders_management_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // only the admin can manage the allowlist
+        let msg = ExecuteMsg::AddTrustedSender {
+            chain_id: "80001".to_string(),
+            sender: "0xAAA".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        mock_trust_sender(deps.as_mut(), "80001", "0xAAA");
+        mock_trust_sender(deps.as_mut(), "80001", "0xbbb");
+        mock_trust_sender(deps.as_mut(), "80001", "0xccc");
+        mock_trust_sender(deps.as_mut(), "43113", "0xddd");
+
+        // hex senders are matched case insensitively
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsTrustedSender {
+                chain_id: "80001".to_string(),
+                sender: "0xaaa".to_string(),
+            },
+        )
+        .unwrap();
+        let value: IsTrustedSenderResponse = from_binary(&res).unwrap();
+        assert!(value.trusted);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TrustedSenders {
+                chain_id: "80001".to_string(),
+                start_after: Some("0xaaa".to_string()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+        let value: TrustedSendersResponse = from_binary(&res).unwrap();
+        assert_eq!(value.senders, vec!["0xbbb".to_string()]);
+
+        let msg = ExecuteMsg::RemoveTrustedSender {
+            chain_id: "80001".to_string(),
+            sender: "0xBBB".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles RemoveTrustedSender message");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TrustedSenders {
+                chain_id: "80001".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: TrustedSendersResponse = from_binary(&res).unwrap();
+        assert_eq!(value.senders, vec!["0xaaa".to_string(), "0xccc".to_string()]);
+    }
+
+    #[test]
+    fn outbound_requests_are_tracked_until_acked() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        mock_i_send(deps.as_mut(), "80001");
+        mock_i_send_reply(deps.as_mut(), 7);
+        mock_i_send(deps.as_mut(), "43113");
+        mock_i_send_reply(deps.as_mut(), 8);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequest {
+                request_identifier: 7,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestResponse = from_binary(&res).unwrap();
+        assert_eq!(value.dest_chain_id, "80001");
+        assert_eq!(value.handler_address, "0xhandler");
+        assert_eq!(value.caller, "creator");
+        assert_eq!(value.height, mock_env().block.height);
+        assert_eq!(value.status, RequestStatus::Pending);
+
+        mock_i_ack(deps.as_mut(), 7, true).expect("contract successfully handles IAck message");
+
+        // an ack moves the request to its final state, only once
+        match mock_i_ack(deps.as_mut(), 7, false) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::RequestAlreadyAcked { request_identifier }) => {
+                assert_eq!(request_identifier, 7)
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequestsByStatus {
+                status: RequestStatus::Pending,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestsResponse = from_binary(&res).unwrap();
+        let identifiers: Vec<u64> = value.requests.iter().map(|r| r.request_identifier).collect();
+        assert_eq!(identifiers, vec![8]);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequestsByChain {
+                dest_chain_id: "80001".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestsResponse = from_binary(&res).unwrap();
+        assert_eq!(value.requests.len(), 1);
+        assert_eq!(value.requests[0].request_identifier, 7);
+        assert_eq!(value.requests[0].status, RequestStatus::AckSuccess);
+    }
+
+    #[test]
+    fn request_result_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        mock_i_send(deps.as_mut(), "80001");
+        mock_i_send_reply(deps.as_mut(), 7);
+
+        // nothing to report before the ack
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequestResult {
+                request_identifier: 7,
+            },
+        )
+        .unwrap();
+        let value: ResolveResultResponse = from_binary(&res).unwrap();
+        assert_eq!(value.exec_status, None);
+        assert_eq!(value.result, None);
+
+        mock_i_ack(deps.as_mut(), 7, false).expect("contract successfully handles IAck message");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequestResult {
+                request_identifier: 7,
+            },
+        )
+        .unwrap();
+        let value: ResolveResultResponse = from_binary(&res).unwrap();
+        assert_eq!(value.request_identifier, 7);
+        assert_eq!(value.exec_status, Some(false));
+        assert_eq!(value.result, Some(Binary::from(b"done".to_vec())));
+    }
+
+    #[test]
+    fn i_send_packs_structured_metadata() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let metadata = RequestMetadata {
+            gas_limit: 300000,
+            gas_price: 60000000000,
+            ack_gas_limit: 200000,
+            ack_gas_price: 60000000000,
+            relayer_fees: Uint128::new(1000),
+            ack_type: 3,
+            is_read_call: false,
+            asm_address: "".to_string(),
+        };
+        let msg = ExecuteMsg::ISend {
+            version: 1,
+            route_amount: 0,
+            route_recipient: "".to_string(),
+            dest_chain_id: "80001".to_string(),
+            request_metadata: None,
+            metadata: Some(metadata),
+            handler_address: "0xhandler".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone())
+            .expect("contract successfully handles ISend message");
+
+        let gateway_msg: GatewayMsg = match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => from_binary(msg).unwrap(),
+            other => panic!("Unexpected message: {:?}", other),
+        };
+        let expected = get_request_metadata(
+            300000,
+            60000000000,
+            200000,
+            60000000000,
+            1000,
+            3,
+            false,
+            "".to_string(),
+        );
+        match gateway_msg {
+            GatewayMsg::ISend { request_metadata, .. } => assert_eq!(request_metadata, expected),
+            other => panic!("Unexpected gateway message: {:?}", other),
+        }
+        let gas_limit = res.events[0]
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "gas_limit")
+            .unwrap();
+        assert_eq!(gas_limit.value, "300000");
+
+        // the metadata that went out can be inspected once the request is tracked
+        mock_i_send_reply(deps.as_mut(), 7);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequestMetadata {
+                request_identifier: 7,
+            },
+        )
+        .unwrap();
+        let value: RequestMetadata = from_binary(&res).unwrap();
+        assert_eq!(value.relayer_fees, Uint128::new(1000));
+        assert_eq!(value.ack_type, 3);
+
+        // raw and structured metadata can't be mixed
+        let mut both = msg.clone();
+        if let ExecuteMsg::ISend { request_metadata, .. } = &mut both {
+            *request_metadata = Some(expected);
+        }
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), both) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidRequestMetadata { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // raw metadata in another layout goes out unchanged and is emitted as hex
+        let mut raw = msg.clone();
+        if let ExecuteMsg::ISend { request_metadata, metadata, .. } = &mut raw {
+            *request_metadata = Some(Binary::from(vec![0xab, 0xcd]));
+            *metadata = None;
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), raw)
+            .expect("contract successfully handles ISend message");
+        let request_metadata = res.events[0]
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "request_metadata")
+            .unwrap();
+        assert_eq!(request_metadata.value, "abcd");
+
+        // and unknown ack types are rejected
+        let mut invalid = msg;
+        if let ExecuteMsg::ISend { metadata: Some(metadata), .. } = &mut invalid {
+            metadata.ack_type = 4;
+        }
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), invalid) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidRequestMetadata { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_i_send_from_non_admin() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_set_chain(deps.as_mut(), "80001", "0xabcdef0123456789abcdef0123456789abcdef01");
+
+        // packets go out as this contract, so only the admin can send them
+        let request = ISendRequest {
+            version: 1,
+            route_amount: 0,
+            route_recipient: "".to_string(),
+            dest_chain_id: "80001".to_string(),
+            request_metadata: Some(Binary::from(vec![0u8; 50])),
+            metadata: None,
+            handler_address: "0xhandler".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+        };
+        let msgs = [
+            ExecuteMsg::ISend {
+                version: 1,
+                route_amount: 0,
+                route_recipient: "".to_string(),
+                dest_chain_id: "80001".to_string(),
+                request_metadata: Some(Binary::from(vec![0u8; 50])),
+                metadata: None,
+                handler_address: "0xhandler".to_string(),
+                payload: Binary::from(b"payload".to_vec()),
+            },
+            ExecuteMsg::ISendBatch {
+                requests: vec![request],
+                allow_partial_failure: false,
+            },
+            ExecuteMsg::ISendTo {
+                dest_chain_id: "80001".to_string(),
+                payload: Binary::from(b"payload".to_vec()),
+                handler_address: None,
+                metadata: None,
+                route_amount: None,
+                route_recipient: None,
+            },
+        ];
+        for msg in msgs {
+            match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg) {
+                Ok(_) => panic!("Must return error"),
+                Err(ContractError::NotAdmin {}) => {}
+                Err(e) => panic!("Unexpected error: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn i_query_returns_abi_tuples() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(3, "token"), coin(4, "token"));
+        mock_alice_registers_name(deps.as_mut(), &coins(3, "token"));
+
+        let i_query = |deps: Deps, msg: &str| {
+            let packet = Binary::from(encode(&[Token::Bytes(msg.as_bytes().to_vec())]));
+            let res = query(deps, mock_env(), QueryMsg::IQuery { packet }).unwrap();
+            from_binary::<Binary>(&res).unwrap()
+        };
+
+        let res = i_query(deps.as_ref(), r#"{"resolve_record": {"name": "alice"}}"#);
+        assert_eq!(abi_decode_resolve_record(&res).unwrap(), (true, "alice_key".to_string()));
+
+        let res = i_query(deps.as_ref(), r#"{"resolve_record": {"name": "bob"}}"#);
+        assert_eq!(abi_decode_resolve_record(&res).unwrap(), (false, "".to_string()));
+
+        let res = i_query(deps.as_ref(), r#"{"config": {}}"#);
+        assert_eq!(
+            abi_decode_config(&res).unwrap(),
+            (Some(coin(3, "token")), Some(coin(4, "token")))
+        );
+    }
+
+    #[test]
+    fn name_targets_work() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+        mock_set_chain(deps.as_mut(), "80001", "0x1b1ad36f42e5b6c1d7dd8bfc9d3d8a8bf6b8b2a3");
+
+        let set_target = |chain_id: &str, address: &str| ExecuteMsg::SetTarget {
+            name: "alice".to_string(),
+            chain_id: chain_id.to_string(),
+            address: address.to_string(),
+        };
+
+        // only the owner can set targets
+        let msg = set_target("80001", "0x1B1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2A3");
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // the address has to match the family of the chain
+        let msg = set_target("80001", "alice_wallet");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidAddress { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = set_target("43113", "0x1B1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2A3");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::ChainNotRegistered { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let msg = set_target("80001", "0x1B1ad36F42E5b6C1D7Dd8BfC9d3D8A8bf6b8b2A3");
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles SetTarget message");
+        let msg = set_target(&mock_env().block.chain_id, "alice_wallet");
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles SetTarget message");
+
+        let resolve = |deps: Deps, chain_id: Option<&str>| {
+            let msg = QueryMsg::ResolveRecord {
+                name: "alice".to_string(),
+                chain_id: chain_id.map(str::to_string),
+            };
+            let res = query(deps, mock_env(), msg).unwrap();
+            from_binary::<ResolveRecordResponse>(&res).unwrap()
+        };
+        let value = resolve(deps.as_ref(), Some("80001"));
+        assert_eq!(Some("alice_key".to_string()), value.address);
+        assert_eq!(
+            Some("0x1b1ad36f42e5b6c1d7dd8bfc9d3d8a8bf6b8b2a3".to_string()),
+            value.target
+        );
+        let value = resolve(deps.as_ref(), Some(&mock_env().block.chain_id));
+        assert_eq!(Some("alice_wallet".to_string()), value.target);
+        assert_eq!(None, resolve(deps.as_ref(), None).target);
+
+        // remote chains look up targets through IQuery
+        let msg = r#"{"resolve_record": {"name": "alice", "chain_id": "80001"}}"#;
+        let packet = Binary::from(encode(&[Token::Bytes(msg.as_bytes().to_vec())]));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::IQuery { packet }).unwrap();
+        assert_eq!(
+            abi_decode_resolve_record(&from_binary(&res).unwrap()).unwrap(),
+            (true, "0x1b1ad36f42e5b6c1d7dd8bfc9d3d8a8bf6b8b2a3".to_string())
+        );
+
+        let msg = ExecuteMsg::RemoveTarget {
+            name: "alice".to_string(),
+            chain_id: "80001".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles RemoveTarget message");
+        assert_eq!(None, resolve(deps.as_ref(), Some("80001")).target);
+
+        // targets don't survive a transfer
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles Transfer message");
+        assert_eq!(None, resolve(deps.as_ref(), Some(&mock_env().block.chain_id)).target);
+    }
+
+    fn query_reverse_resolve(
+        deps: Deps,
+        env: Env,
+        address: &str,
+        chain_id: Option<&str>,
+    ) -> Option<String> {
+        let msg = QueryMsg::ReverseResolve {
+            address: address.to_string(),
+            chain_id: chain_id.map(str::to_string),
+        };
+        let res = query(deps, env, msg).unwrap();
+        from_binary::<ReverseResolveResponse>(&res).unwrap().name
+    }
+
+    #[test]
+    fn primary_name_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // only the owner can make a name primary
+        let msg = ExecuteMsg::SetPrimaryName {
+            name: "alice".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles SetPrimaryName message");
+
+        let name = query_reverse_resolve(deps.as_ref(), mock_env(), "alice_key", None);
+        assert_eq!(Some("alice".to_string()), name);
+
+        let msg = r#"{"reverse_resolve": {"address": "alice_key"}}"#;
+        let packet = Binary::from(encode(&[Token::Bytes(msg.as_bytes().to_vec())]));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::IQuery { packet }).unwrap();
+        assert_eq!(
+            abi_decode_resolve_record(&from_binary(&res).unwrap()).unwrap(),
+            (true, "alice".to_string())
+        );
+
+        // an expired name doesn't reverse resolve
+        let env = mock_env_after(SECONDS_PER_YEAR);
+        assert_eq!(None, query_reverse_resolve(deps.as_ref(), env, "alice_key", None));
+
+        // and a transfer clears the primary name
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "bob_key".to_string(),
+            to_chain_id: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles Transfer message");
+        assert_eq!(None, query_reverse_resolve(deps.as_ref(), mock_env(), "alice_key", None));
+        assert_eq!(None, query_reverse_resolve(deps.as_ref(), mock_env(), "bob_key", None));
+    }
+
+    #[test]
+    fn remote_primary_name_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+
+        for (request_identifier, msg) in [
+            CustomExecuteMsg::Register {
+                name: "remote".to_string(),
+                years: None,
+            },
+            CustomExecuteMsg::SetPrimaryName {
+                name: "remote".to_string(),
+            },
+        ]
+        .iter()
+        .enumerate()
+        {
+            let msg = ExecuteMsg::IReceive {
+                src_chain_id: "80001".to_string(),
+                request_sender: "0xSender".to_string(),
+                packet: mock_packet(msg),
+                request_identifier: Some(request_identifier as u64),
+            };
+            let res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+                .expect("contract successfully handles IReceive message");
+            let (success, _, _) = abi_decode_ack(&res.data.unwrap()).unwrap();
+            assert!(success);
+        }
+
+        let name = query_reverse_resolve(deps.as_ref(), mock_env(), "0xSENDER", Some("80001"));
+        assert_eq!(Some("remote".to_string()), name);
+        assert_eq!(None, query_reverse_resolve(deps.as_ref(), mock_env(), "0xsender", None));
+    }
+
+    #[test]
+    fn subdomains_work() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        // subdomains can't be registered
+        let msg = ExecuteMsg::Register {
+            name: "pay.alice".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotParentOwner { parent }) => assert_eq!("alice", parent),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // only the parent owner can create them
+        let create_subdomain = |label: &str, owner: &str| ExecuteMsg::CreateSubdomain {
+            parent: "alice".to_string(),
+            label: label.to_string(),
+            owner: owner.to_string(),
+            owner_chain_id: None,
+        };
+        let msg = create_subdomain("pay", "bob_key");
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotParentOwner { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = create_subdomain("p.ay", "bob_key");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidCharacter { c }) => assert_eq!('.', c),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        // labels are only bound by the length of the full name
+        let msg = create_subdomain(&"a".repeat(59), "bob_key");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTooLong { length, .. }) => assert_eq!(65, length),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = create_subdomain("", "bob_key");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTooShort { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = create_subdomain("x", "bob_key");
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles CreateSubdomain message");
+        assert_name_owner(deps.as_ref(), "x.alice", "bob_key");
+
+        for label in ["pay", "shop", "mail"] {
+            let msg = create_subdomain(label, "bob_key");
+            let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+                .expect("contract successfully handles CreateSubdomain message");
+        }
+        let msg = create_subdomain("pay", "carol_key");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTaken { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        assert_name_owner(deps.as_ref(), "pay.alice", "bob_key");
+
+        // the subdomain owner manages its own records
+        let msg = ExecuteMsg::SetPrimaryName {
+            name: "pay.alice".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg)
+            .expect("contract successfully handles SetPrimaryName message");
+
+        let subdomains = |deps: Deps, start_after: Option<&str>| {
+            let msg = QueryMsg::Subdomains {
+                parent: "alice".to_string(),
+                start_after: start_after.map(str::to_string),
+                limit: Some(2),
+            };
+            let res = query(deps, mock_env(), msg).unwrap();
+            from_binary::<SubdomainsResponse>(&res).unwrap().subdomains
+        };
+        assert_eq!(vec!["mail.alice", "pay.alice"], subdomains(deps.as_ref(), None));
+        assert_eq!(vec!["shop.alice", "x.alice"], subdomains(deps.as_ref(), Some("pay")));
+
+        // the parent owner can take a subdomain back
+        let msg = ExecuteMsg::ReclaimSubdomain {
+            name: "pay.alice".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotParentOwner { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles ReclaimSubdomain message");
+        assert_eq!(None, query_reverse_resolve(deps.as_ref(), mock_env(), "bob_key", None));
+        assert_eq!(vec!["mail.alice", "shop.alice"], subdomains(deps.as_ref(), None));
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ResolveRecord {
+                name: "pay.alice".to_string(),
+                chain_id: None,
+            },
+        )
+        .unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.address);
+
+        // the next owner of a released parent starts without subdomains
+        let env = mock_env_after(SECONDS_PER_YEAR + GRACE_PERIOD_SECONDS);
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("carol_key", &[]), msg)
+            .expect("contract successfully handles Register message");
+        let msg = QueryMsg::Subdomains {
+            parent: "alice".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), env, msg).unwrap();
+        assert!(from_binary::<SubdomainsResponse>(&res).unwrap().subdomains.is_empty());
+        assert!(query_tokens(deps.as_ref(), "bob_key").is_empty());
+    }
+
+    #[test]
+    fn subdomain_lease_follows_parent() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        let msg = ExecuteMsg::CreateSubdomain {
+            parent: "alice".to_string(),
+            label: "pay".to_string(),
+            owner: "bob_key".to_string(),
+            owner_chain_id: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles CreateSubdomain message");
+
+        let msg = ExecuteMsg::Renew {
+            name: "alice".to_string(),
+            years: 1,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles Renew message");
+
+        let expires = |deps: Deps, name: &str| {
+            let msg = QueryMsg::ResolveRecord {
+                name: name.to_string(),
+                chain_id: None,
+            };
+            let res = query(deps, mock_env(), msg).unwrap();
+            from_binary::<ResolveRecordResponse>(&res).unwrap().expires
+        };
+        let parent_expires = expires(deps.as_ref(), "alice");
+        assert_ne!(parent_expires, expires(deps.as_ref(), "pay.alice"));
+
+        // renewing a subdomain is free and catches up with the parent
+        let msg = ExecuteMsg::Renew {
+            name: "pay.alice".to_string(),
+            years: 1,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("anyone", &coins(2, "token")), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Payment(_)) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg)
+            .expect("contract successfully handles Renew message");
+        assert_eq!(parent_expires, expires(deps.as_ref(), "pay.alice"));
+    }
+
+    fn query_tokens(deps: Deps, owner: &str) -> Vec<String> {
+        let msg = QueryMsg::Tokens {
+            owner: owner.to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps, mock_env(), msg).unwrap();
+        from_binary::<TokensResponse>(&res).unwrap().tokens
+    }
+
+    #[test]
+    fn cw721_transfer_works() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+        mock_alice_registers_name(deps.as_mut(), &coins(2, "token"));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::NftInfo {
+                token_id: "alice".to_string(),
+            },
+        )
+        .unwrap();
+        let value: NftInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(mock_env().block.chain_id, value.extension.owner_chain_id);
+        assert_eq!(vec!["alice"], query_tokens(deps.as_ref(), "alice_key"));
+
+        // the transfer price applies to cw721 transfers too
+        let transfer_nft = |recipient: &str| ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: "alice".to_string(),
+        };
+        let msg = transfer_nft("bob_key");
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InsufficientFundsSend {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let info = mock_info("bob_key", &coins(2, "token"));
+        match execute(deps.as_mut(), mock_env(), info, transfer_nft("bob_key")) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // an approved spender can move the name
+        let approve = |expires: Expiration| ExecuteMsg::Approve {
+            spender: "bob_key".to_string(),
+            token_id: "alice".to_string(),
+            expires: Some(expires),
+        };
+        let msg = approve(Expiration::AtHeight(mock_env().block.height));
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::ApprovalExpired {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = approve(Expiration::AtHeight(mock_env().block.height + 10));
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles Approve message");
+
+        let owner_of = |deps: Deps| {
+            let msg = QueryMsg::OwnerOf {
+                token_id: "alice".to_string(),
+                include_expired: None,
+            };
+            let res = query(deps, mock_env(), msg).unwrap();
+            from_binary::<OwnerOfResponse>(&res).unwrap()
+        };
+        let value = owner_of(deps.as_ref());
+        assert_eq!("alice_key", value.owner);
+        assert_eq!(mock_env().block.chain_id, value.owner_chain_id);
+        assert_eq!(1, value.approvals.len());
+
+        let info = mock_info("bob_key", &coins(2, "token"));
+        let _res = execute(deps.as_mut(), mock_env(), info, transfer_nft("carol_key"))
+            .expect("contract successfully handles TransferNft message");
+
+        // approvals don't survive the transfer
+        let value = owner_of(deps.as_ref());
+        assert_eq!("carol_key", value.owner);
+        assert!(value.approvals.is_empty());
+        assert!(query_tokens(deps.as_ref(), "alice_key").is_empty());
+        assert_eq!(vec!["alice"], query_tokens(deps.as_ref(), "carol_key"));
+
+        // the existing Transfer message keeps working
+        let msg = ExecuteMsg::Transfer {
+            name: "alice".to_string(),
+            to: "alice_key".to_string(),
+            to_chain_id: None,
+        };
+        let info = mock_info("carol_key", &coins(2, "token"));
+        let _res = execute(deps.as_mut(), mock_env(), info, msg)
+            .expect("contract successfully handles Transfer message");
+        assert_eq!("alice_key", owner_of(deps.as_ref()).owner);
+    }
+
+    #[test]
+    fn cw721_send_calls_receiver() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        let msg = ExecuteMsg::SendNft {
+            contract: "marketplace".to_string(),
+            token_id: "alice".to_string(),
+            msg: Binary::from(b"list"),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles SendNft message");
+        let hook = ReceiverExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: "alice_key".to_string(),
+            token_id: "alice".to_string(),
+            msg: Binary::from(b"list"),
+        });
+        assert_eq!(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "marketplace".to_string(),
+                msg: to_binary(&hook).unwrap(),
+                funds: vec![],
+            }),
+            res.messages[0].msg
+        );
+        assert_eq!(vec!["alice"], query_tokens(deps.as_ref(), "marketplace"));
+
+        // all tokens leaves out expired names
+        let msg = QueryMsg::AllTokens {
+            start_after: None,
+            limit: None,
+        };
+        let res = query(deps.as_ref(), mock_env_after(SECONDS_PER_YEAR), msg).unwrap();
+        let value: TokensResponse = from_binary(&res).unwrap();
+        assert!(value.tokens.is_empty());
+
+        // a page scans at most limit names, expired ones leave it short
+        let msg = ExecuteMsg::Register {
+            name: "bobby".to_string(),
+            years: Some(2),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("bob_key", &coins(4, "token")), msg)
+            .expect("contract successfully handles Register message");
+        let all_tokens = |deps: Deps, start_after: Option<&str>| {
+            let msg = QueryMsg::AllTokens {
+                start_after: start_after.map(str::to_string),
+                limit: Some(1),
+            };
+            let res = query(deps, mock_env_after(SECONDS_PER_YEAR), msg).unwrap();
+            from_binary::<TokensResponse>(&res).unwrap().tokens
+        };
+        assert!(all_tokens(deps.as_ref(), None).is_empty());
+        assert_eq!(vec!["bobby"], all_tokens(deps.as_ref(), Some("alice")));
+    }
+
+    #[test]
+    fn set_dapp_metadata_reply_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let msg = ExecuteMsg::SetDappMetadata {
+            fee_payer_address: "fee_payer".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles SetDappMetadata message");
+        assert_eq!(res.messages[0].id, SET_DAPP_METADATA_ID);
+
+        // nothing is recorded until the gateway replies
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::DappMetadata {}).unwrap();
+        let value: DappMetadataResponse = from_binary(&res).unwrap();
+        assert_eq!(value.fee_payer_address, None);
+
+        // a reply without data must not panic
+        let msg = Reply {
+            id: SET_DAPP_METADATA_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        let _res = reply(deps.as_mut(), mock_env(), msg)
+            .expect("contract successfully handles SetDappMetadata reply");
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::DappMetadata {}).unwrap();
+        let value: DappMetadataResponse = from_binary(&res).unwrap();
+        assert_eq!(value.fee_payer_address, Some("fee_payer".to_string()));
+        assert_eq!(value.gateway_response, None);
+    }
+
+    #[test]
+    fn i_send_reply_failure_policy_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let failed_reply = || Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        };
+
+        // strict by default: the failure reverts the caller's transaction
+        mock_i_send(deps.as_mut(), "80001");
+        match reply(deps.as_mut(), mock_env(), failed_reply()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Std(_)) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // only the admin can change the policy
+        let msg = ExecuteMsg::SetReplyFailurePolicy {
+            policy: ReplyFailurePolicy::Record,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles SetReplyFailurePolicy message");
+
+        mock_i_send(deps.as_mut(), "80001");
+        let res = reply(deps.as_mut(), mock_env(), failed_reply())
+            .expect("contract records the failed request");
+        assert_eq!(res.events[0].ty, "ISendFailed");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::FailedRequests {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: FailedRequestsResponse = from_binary(&res).unwrap();
+        assert_eq!(value.requests.len(), 1);
+        assert_eq!(value.requests[0].id, 1);
+        assert_eq!(value.requests[0].dest_chain_id, "80001");
+        assert_eq!(value.requests[0].caller, "creator");
+        assert_eq!(value.requests[0].error, "SubMsgFailure: out of gas");
+    }
+
+    #[test]
+    fn failed_requests_are_queued_for_retry() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        let msg = ExecuteMsg::SetReplyFailurePolicy {
+            policy: ReplyFailurePolicy::Record,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles SetReplyFailurePolicy message");
+
+        // the gateway rejects the first request
+        mock_i_send(deps.as_mut(), "80001");
+        let failed_reply = Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        };
+        let _res = reply(deps.as_mut(), mock_env(), failed_reply)
+            .expect("contract records the failed request");
+
+        // the second request fails on the destination chain
+        mock_i_send(deps.as_mut(), "43113");
+        mock_i_send_reply(deps.as_mut(), 7);
+        let _res = mock_i_ack(deps.as_mut(), 7, false).expect("contract handles IAck");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RetryQueue {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: RetryQueueResponse = from_binary(&res).unwrap();
+        assert_eq!(value.entries.len(), 2);
+        assert_eq!(value.entries[0].id, 1);
+        assert_eq!(value.entries[0].dest_chain_id, "80001");
+        assert_eq!(value.entries[0].caller, "creator");
+        assert_eq!(
+            value.entries[0].source,
+            RetrySource::SendFailure {
+                failed_request_id: 1
+            }
+        );
+        assert_eq!(value.entries[1].id, 2);
+        assert_eq!(value.entries[1].dest_chain_id, "43113");
+        assert_eq!(
+            value.entries[1].source,
+            RetrySource::AckFailure {
+                request_identifier: 7
+            }
+        );
+    }
+
+    #[test]
+    fn retry_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_i_send(deps.as_mut(), "80001");
+        mock_i_send_reply(deps.as_mut(), 7);
+        let _res = mock_i_ack(deps.as_mut(), 7, false).expect("contract handles IAck");
+
+        // only the original caller or the admin can retry
+        let msg = ExecuteMsg::Retry {
+            ids: vec![1],
+            metadata: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // retry with bumped gas
+        let metadata = RequestMetadata {
+            gas_limit: 500_000,
+            gas_price: 20,
+            ack_gas_limit: 0,
+            ack_gas_price: 0,
+            relayer_fees: Uint128::zero(),
+            ack_type: 0,
+            is_read_call: false,
+            asm_address: "".to_string(),
+        };
+        let msg = ExecuteMsg::Retry {
+            ids: vec![1],
+            metadata: Some(metadata.clone()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles Retry message");
+        assert_eq!(res.messages.len(), 1);
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(contract_addr, GATEWAY);
+                match from_binary(msg).unwrap() {
+                    GatewayMsg::ISend {
+                        dest_chain_id,
+                        request_metadata,
+                        ..
+                    } => {
+                        assert_eq!(dest_chain_id, "80001");
+                        assert_eq!(
+                            request_metadata,
+                            get_request_metadata(500_000, 20, 0, 0, 0, 0, false, "".to_string())
+                        );
+                    }
+                    other => panic!("Unexpected gateway msg: {:?}", other),
+                }
+            }
+            other => panic!("Unexpected msg: {:?}", other),
+        }
+
+        // the retry is tracked under its new identifier and links back to the original request
+        mock_i_send_reply(deps.as_mut(), 8);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequest {
+                request_identifier: 8,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestResponse = from_binary(&res).unwrap();
+        assert_eq!(value.retry_of, Some(1));
+        assert_eq!(
+            value.original_request,
+            Some(RetrySource::AckFailure {
+                request_identifier: 7
+            })
+        );
+        assert_eq!(value.attempt, 1);
+        assert_eq!(value.status, RequestStatus::Pending);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::RetryEntry { id: 1 });
+        assert!(res.is_err());
+
+        // a failed retry goes back in the queue under the same id
+        let _res = mock_i_ack(deps.as_mut(), 8, false).expect("contract handles IAck");
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::RetryEntry { id: 1 }).unwrap();
+        let value: RetryEntryResponse = from_binary(&res).unwrap();
+        assert_eq!(value.attempt, 1);
+
+        // and the next retry still points at the original request
+        let msg = ExecuteMsg::Retry {
+            ids: vec![1],
+            metadata: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles Retry message");
+        mock_i_send_reply(deps.as_mut(), 9);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequest {
+                request_identifier: 9,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            value.original_request,
+            Some(RetrySource::AckFailure {
+                request_identifier: 7
+            })
+        );
+        assert_eq!(value.attempt, 2);
+    }
+
+    #[test]
+    fn fails_on_retry_over_limit() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // only the admin can change the limit
+        let msg = ExecuteMsg::SetMaxRetryAttempts {
+            max_retry_attempts: 1,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles SetMaxRetryAttempts message");
+
+        mock_i_send(deps.as_mut(), "80001");
+        mock_i_send_reply(deps.as_mut(), 7);
+        let _res = mock_i_ack(deps.as_mut(), 7, false).expect("contract handles IAck");
+
+        // the admin may retry anyone's request
+        let msg = ExecuteMsg::Retry {
+            ids: vec![1],
+            metadata: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone())
+            .expect("contract successfully handles Retry message");
+        mock_i_send_reply(deps.as_mut(), 8);
+        let _res = mock_i_ack(deps.as_mut(), 8, false).expect("contract handles IAck");
+
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::RetryLimitReached { id, max_attempts }) => {
+                assert_eq!(id, 1);
+                assert_eq!(max_attempts, 1);
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn i_send_batch_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let msg = ExecuteMsg::ISendBatch {
+            requests: vec![
+                mock_i_send_request("80001"),
+                mock_i_send_request("43113"),
+                mock_i_send_request("5"),
+            ],
+            allow_partial_failure: true,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ISendBatch message");
+        assert_eq!(res.messages.len(), 3);
+        assert_eq!(res.events.len(), 3);
+        assert!(res.messages.iter().all(|msg| msg.id == ISEND_ID));
+
+        // the second entry is rejected by the gateway, the others go through
+        mock_i_send_reply(deps.as_mut(), 7);
+        let failed_reply = Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        };
+        let res = reply(deps.as_mut(), mock_env(), failed_reply)
+            .expect("contract records the failed entry");
+        assert_eq!(res.events[0].ty, "ISendFailed");
+
+        let identifier = to_binary(&9u64).unwrap();
+        let mut data = vec![0x0a, identifier.len() as u8];
+        data.extend(identifier.as_slice());
+        let last_reply = Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(data)),
+            }),
+        };
+        let res = reply(deps.as_mut(), mock_env(), last_reply)
+            .expect("contract successfully handles ISend reply");
+        let value: ISendBatchResponse = from_binary(&res.data.unwrap()).unwrap();
+        assert_eq!(value.entries.len(), 3);
+        assert_eq!(value.entries[0].dest_chain_id, "80001");
+        assert_eq!(value.entries[0].request_identifier, Some(7));
+        assert_eq!(value.entries[1].index, 1);
+        assert_eq!(value.entries[1].dest_chain_id, "43113");
+        assert_eq!(value.entries[1].request_identifier, None);
+        assert_eq!(value.entries[1].failed_request_id, Some(1));
+        assert_eq!(value.entries[2].dest_chain_id, "5");
+        assert_eq!(value.entries[2].request_identifier, Some(9));
+
+        // each reply was matched with its own entry
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequest {
+                request_identifier: 9,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestResponse = from_binary(&res).unwrap();
+        assert_eq!(value.dest_chain_id, "5");
+    }
+
+    #[test]
+    fn fails_on_i_send_batch_entry_failure() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let msg = ExecuteMsg::ISendBatch {
+            requests: vec![mock_i_send_request("80001"), mock_i_send_request("43113")],
+            allow_partial_failure: false,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ISendBatch message");
+        mock_i_send_reply(deps.as_mut(), 7);
+
+        let failed_reply = Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        };
+        match reply(deps.as_mut(), mock_env(), failed_reply) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Std(_)) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fails_on_i_send_batch_invalid_size() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let msg = ExecuteMsg::ISendBatch {
+            requests: vec![],
+            allow_partial_failure: false,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidBatchSize { size, .. }) => assert_eq!(size, 0),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn chain_registry_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        let handler = "0xAbCdEf0123456789aBcDeF0123456789AbCdEf01";
+
+        // only the admin can register chains
+        let msg = ExecuteMsg::SetChain {
+            dest_chain_id: "80001".to_string(),
+            chain_type: ChainType::Evm,
+            handler_address: handler.to_string(),
+            metadata: mock_metadata(300_000),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("frank_key", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NotAdmin {}) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // evm handlers must be 20 byte hex addresses
+        let msg = ExecuteMsg::SetChain {
+            dest_chain_id: "80001".to_string(),
+            chain_type: ChainType::Evm,
+            handler_address: "0xhandler".to_string(),
+            metadata: mock_metadata(300_000),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidHandlerAddress { address }) => {
+                assert_eq!(address, "0xhandler")
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        mock_set_chain(deps.as_mut(), "80001", handler);
+        mock_set_chain(deps.as_mut(), "43113", handler);
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Chain {
+                dest_chain_id: "80001".to_string(),
+            },
+        )
+        .unwrap();
+        let value: ChainResponse = from_binary(&res).unwrap();
+        assert_eq!(value.chain_type, ChainType::Evm);
+        assert_eq!(value.handler_address, handler.to_lowercase());
+        assert_eq!(value.metadata, mock_metadata(300_000));
+
+        let msg = ExecuteMsg::RemoveChain {
+            dest_chain_id: "80001".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles RemoveChain message");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Chains {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let value: ChainsResponse = from_binary(&res).unwrap();
+        assert_eq!(value.chains.len(), 1);
+        assert_eq!(value.chains[0].dest_chain_id, "43113");
+    }
+
+    #[test]
+    fn i_send_to_works() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        let handler = "0xabcdef0123456789abcdef0123456789abcdef01";
+
+        let msg = ExecuteMsg::ISendTo {
+            dest_chain_id: "80001".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+            handler_address: None,
+            metadata: None,
+            route_amount: None,
+            route_recipient: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::ChainNotRegistered { dest_chain_id }) => {
+                assert_eq!(dest_chain_id, "80001")
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // registry defaults are used
+        mock_set_chain(deps.as_mut(), "80001", handler);
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ISendTo message");
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => {
+                assert_eq!(contract_addr, GATEWAY);
+                match from_binary(msg).unwrap() {
+                    GatewayMsg::ISend {
+                        dest_chain_id,
+                        request_metadata,
+                        ..
+                    } => {
+                        assert_eq!(dest_chain_id, "80001");
+                        assert_eq!(
+                            request_metadata,
+                            get_request_metadata(300_000, 10, 0, 0, 0, 0, false, "".to_string())
+                        );
+                    }
+                    other => panic!("Unexpected gateway msg: {:?}", other),
+                }
+            }
+            other => panic!("Unexpected msg: {:?}", other),
+        }
+        mock_i_send_reply(deps.as_mut(), 7);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::OutboundRequest {
+                request_identifier: 7,
+            },
+        )
+        .unwrap();
+        let value: OutboundRequestResponse = from_binary(&res).unwrap();
+        assert_eq!(value.handler_address, handler);
+
+        // explicit values override the registry
+        let other_handler = "0x0000000000000000000000000000000000000001";
+        let msg = ExecuteMsg::ISendTo {
+            dest_chain_id: "80001".to_string(),
+            payload: Binary::from(b"payload".to_vec()),
+            handler_address: Some(other_handler.to_string()),
+            metadata: Some(mock_metadata(900_000)),
+            route_amount: None,
+            route_recipient: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+            .expect("contract successfully handles ISendTo message");
+        let attribute = |key: &str| {
+            res.events[0]
+                .attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.clone())
+        };
+        assert_eq!(attribute("handler_address"), Some(other_handler.to_string()));
+        assert_eq!(attribute("gas_limit"), Some("900000".to_string()));
+    }
+
+    #[test]
+    fn fails_on_i_send_reply_without_data() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_i_send(deps.as_mut(), "80001");
+
+        let msg = Reply {
+            id: ISEND_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        match reply(deps.as_mut(), mock_env(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Std(_)) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn migrate_legacy_state_works() {
+        let mut deps = mock_dependencies();
+
+        // state as written by the unversioned contract
+        let legacy_config = LegacyConfig {
+            purchase_price: Some(coin(2, "token")),
+            transfer_price: None,
+        };
+        LEGACY_CONFIG.save(deps.as_mut().storage, &legacy_config).unwrap();
+        let record = LegacyNameRecord {
+            owner: Addr::unchecked("alice_key"),
+        };
+        LEGACY_NAME_RESOLVER.save(deps.as_mut().storage, b"alice", &record).unwrap();
+        let pending = PendingRequests {
+            requests: vec![3, 4],
+        };
+        PENDING.save(deps.as_mut().storage, &pending).unwrap();
+        REQUEST.save(deps.as_mut().storage, &Binary::from(b"request".to_vec())).unwrap();
+        RESULT.save(deps.as_mut().storage, &Binary::from(b"result".to_vec())).unwrap();
+
+        // legacy instances need the settings they lack
+        let msg = MigrateMsg { legacy: None };
+        match migrate(deps.as_mut(), mock_env(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidMigration { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let msg = MigrateMsg {
+            legacy: Some(LegacyMigrateMsg {
+                gateway_address: GATEWAY.to_string(),
+                admin: "creator".to_string(),
+                reply_failure_policy: None,
+                max_retry_attempts: None,
+            }),
+        };
+        let _res = migrate(deps.as_mut(), mock_env(), msg.clone())
+            .expect("contract successfully migrates legacy state");
+
+        assert_config_state(
+            deps.as_ref(),
+            Config {
+                purchase_price: Some(coin(2, "token")),
+                transfer_price: None,
+                gateway_address: Addr::unchecked(GATEWAY),
+                reply_failure_policy: ReplyFailurePolicy::Strict,
+                max_retry_attempts: DEFAULT_MAX_RETRY_ATTEMPTS,
+            },
+        );
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::LoadStates {}).unwrap();
+        let value: LoadStatesResponse = from_binary(&res).unwrap();
+        assert_eq!(value.pending, vec![3, 4]);
+        assert_eq!(value.request, Binary::from(b"request".to_vec()));
+        assert_eq!(value.result, Binary::from(b"result".to_vec()));
+
+        // the legacy migration runs once
+        match migrate(deps.as_mut(), mock_env(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidMigration { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn migrate_checks_contract_version() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        let msg = MigrateMsg { legacy: None };
+        let _res = migrate(deps.as_mut(), mock_env(), msg.clone())
+            .expect("contract successfully migrates to the same version");
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        match migrate(deps.as_mut(), mock_env(), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::CannotDowngrade { previous_version, .. }) => {
+                assert_eq!(previous_version, "99.0.0")
+            }
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        set_contract_version(deps.as_mut().storage, "other-contract", "0.1.0").unwrap();
+        match migrate(deps.as_mut(), mock_env(), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::InvalidMigration { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn migrate_indexes_names_of_older_versions() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // records written before the owner index existed
+        let record = NameRecord {
+            owner: Owner::local(&mock_env(), &Addr::unchecked("alice_key")),
+            expires: mock_env().block.time.plus_seconds(SECONDS_PER_YEAR),
+        };
+        let unindexed: Map<&[u8], NameRecord> = Map::new("name_resolver");
+        unindexed.save(deps.as_mut().storage, b"alice", &record).unwrap();
+        assert!(query_tokens(deps.as_ref(), "alice_key").is_empty());
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.14.0").unwrap();
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy: None })
+            .expect("contract successfully migrates");
+        assert_eq!(vec!["alice".to_string()], query_tokens(deps.as_ref(), "alice_key"));
+    }
+
+    #[test]
+    fn migrate_leases_names_of_older_versions() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // records written before leases existed
+        let record = UnleasedNameRecord {
+            owner: Owner::local(&mock_env(), &Addr::unchecked("alice_key")),
+        };
+        UNLEASED_NAME_RESOLVER
+            .save(deps.as_mut().storage, b"alice", &record)
+            .unwrap();
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.12.0").unwrap();
+        let _res = migrate(deps.as_mut(), mock_env(), MigrateMsg { legacy: None })
+            .expect("contract successfully migrates");
+        assert_name_owner(deps.as_ref(), "alice", "alice_key");
+        assert_eq!(vec!["alice".to_string()], query_tokens(deps.as_ref(), "alice_key"));
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ResolveRecord {
+                name: "alice".to_string(),
+                chain_id: None,
+            },
+        )
+        .unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        let expires = mock_env().block.time.plus_seconds(SECONDS_PER_YEAR);
+        assert_eq!(Some(expires), value.expires);
+    }
+
+    fn mock_start_auction(deps: DepsMut, name: &str, min_bid: Coin) {
+        let funds = [min_bid.clone()];
+        let msg = ExecuteMsg::StartAuction {
+            name: name.to_string(),
+            min_bid,
+        };
+        let _res = execute(deps, mock_env(), mock_info("auctioneer", &funds), msg)
+            .expect("contract successfully handles StartAuction message");
+    }
+
+    fn query_auction(deps: Deps, env: Env, name: &str) -> AuctionResponse {
+        let res = query(
+            deps,
+            env,
+            QueryMsg::Auction {
+                name: name.to_string(),
+            },
+        )
+        .unwrap();
+        from_binary(&res).unwrap()
+    }
+
+    #[test]
+    fn auction_works() {
+        let mut deps = mock_dependencies();
+        mock_init_with_price(deps.as_mut(), coin(2, "token"), coin(2, "token"));
+
+        // the auction can't undercut the purchase price
+        let msg = ExecuteMsg::StartAuction {
+            name: "alice".to_string(),
+            min_bid: coin(1, "token"),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("auctioneer", &coins(1, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::BidTooLow { minimum }) => assert_eq!("2token", minimum),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // the starter has to escrow the opening bid
+        let msg = ExecuteMsg::StartAuction {
+            name: "alice".to_string(),
+            min_bid: coin(5, "token"),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("auctioneer", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::Payment(_)) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        match execute(deps.as_mut(), mock_env(), mock_info("auctioneer", &coins(4, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::BidTooLow { minimum }) => assert_eq!("5token", minimum),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        mock_start_auction(deps.as_mut(), "alice", coin(5, "token"));
+        let auction = query_auction(deps.as_ref(), mock_env(), "alice");
+        assert_eq!(Some("auctioneer".to_string()), auction.highest_bidder);
+        assert_eq!(Some(coin(5, "token")), auction.highest_bid);
+
+        // bids have to beat the opening bid
+        let msg = ExecuteMsg::Bid {
+            name: "alice".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &coins(5, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::BidTooLow { minimum }) => assert_eq!("6token", minimum),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let msg = ExecuteMsg::Bid {
+            name: "alice".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob_key", &coins(6, "token")), msg)
+            .expect("contract successfully handles Bid message");
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "auctioneer".to_string(),
+                amount: coins(5, "token"),
+            }),
+            res.messages[0].msg
+        );
+
+        // a higher bid refunds the outbid bidder
+        let msg = ExecuteMsg::Bid {
+            name: "alice".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &coins(6, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::BidTooLow { minimum }) => assert_eq!("7token", minimum),
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+        let msg = ExecuteMsg::Bid {
+            name: "alice".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice_key", &coins(8, "token")), msg)
+            .expect("contract successfully handles Bid message");
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "bob_key".to_string(),
+                amount: coins(6, "token"),
+            }),
+            res.messages[0].msg
+        );
+
+        let auction = query_auction(deps.as_ref(), mock_env(), "alice");
+        assert_eq!(Some("alice_key".to_string()), auction.highest_bidder);
+        assert_eq!(Some(coin(8, "token")), auction.highest_bid);
+        assert!(!auction.ended);
+
+        // the name can't be registered while it is auctioned
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("bob_key", &coins(2, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::AuctionInProgress { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // and the auction can't be settled before its end
+        let msg = ExecuteMsg::SettleAuction {
+            name: "alice".to_string(),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::AuctionNotEnded { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let env = mock_env_after(AUCTION_DURATION_SECONDS);
+        let msg = ExecuteMsg::Bid {
+            name: "alice".to_string(),
+        };
+        match execute(deps.as_mut(), env.clone(), mock_info("bob_key", &coins(9, "token")), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::AuctionEnded { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let msg = ExecuteMsg::SettleAuction {
+            name: "alice".to_string(),
+        };
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg)
+            .expect("contract successfully handles SettleAuction message");
+
+        let res = query(
+            deps.as_ref(),
+            env,
+            QueryMsg::ResolveRecord {
+                name: "alice".to_string(),
+                chain_id: None,
+            },
+        )
+        .unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(Some("alice_key".to_string()), value.address);
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Auction {
+                name: "alice".to_string(),
+            },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn fails_on_auction_of_taken_name() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_alice_registers_name(deps.as_mut(), &[]);
+
+        let msg = ExecuteMsg::StartAuction {
+            name: "alice".to_string(),
+            min_bid: coin(5, "token"),
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("auctioneer", &[]), msg) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::NameTaken { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        // a released name can be auctioned
+        let env = mock_env_after(SECONDS_PER_YEAR + GRACE_PERIOD_SECONDS);
+        let msg = ExecuteMsg::StartAuction {
+            name: "alice".to_string(),
+            min_bid: coin(5, "token"),
+        };
+        let _res = execute(deps.as_mut(), env, mock_info("auctioneer", &coins(5, "token")), msg)
+            .expect("contract successfully handles StartAuction message");
+    }
+
+    #[test]
+    fn register_clears_ended_auction_without_bids() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+
+        // auctions started before the opening bid was escrowed may have no bids
+        let auction = Auction {
+            min_bid: coin(5, "token"),
+            end: mock_env().block.time.plus_seconds(AUCTION_DURATION_SECONDS),
+            highest_bid: None,
+        };
+        AUCTIONS.save(deps.as_mut().storage, b"alice", &auction).unwrap();
+
+        let msg = ExecuteMsg::Register {
+            name: "alice".to_string(),
+            years: None,
+        };
+        match execute(deps.as_mut(), mock_env(), mock_info("alice_key", &[]), msg.clone()) {
+            Ok(_) => panic!("Must return error"),
+            Err(ContractError::AuctionInProgress { .. }) => {}
+            Err(e) => panic!("Unexpected error: {:?}", e),
+        }
+
+        let env = mock_env_after(AUCTION_DURATION_SECONDS);
+        let _res = execute(deps.as_mut(), env.clone(), mock_info("alice_key", &[]), msg)
+            .expect("contract successfully handles Register message");
+        assert!(!AUCTIONS.has(deps.as_ref().storage, b"alice"));
+    }
+
+    #[test]
+    fn remote_bidders_are_credited() {
+        let mut deps = mock_dependencies();
+        mock_init_no_price(deps.as_mut());
+        mock_trust_sender(deps.as_mut(), "80001", "0xsender");
+        mock_start_auction(deps.as_mut(), "alice", coin(5, "token"));
+
+        // the gateway forwards the funds of a remote bid
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xSender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Bid {
+                name: "alice".to_string(),
+            }),
+            request_identifier: None,
+        };
+        let _res = mock_i_receive(&mut deps, mock_info(GATEWAY, &coins(6, "token")), msg)
+            .expect("contract successfully handles IReceive message");
+        let auction = query_auction(deps.as_ref(), mock_env(), "alice");
+        assert_eq!(Some("0xsender".to_string()), auction.highest_bidder);
+        assert_eq!(Some("80001".to_string()), auction.highest_bidder_chain_id);
+
+        // the outbid remote bidder gets credits instead of a bank refund
+        let msg = ExecuteMsg::Bid {
+            name: "alice".to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob_key", &coins(7, "token")), msg)
+            .expect("contract successfully handles Bid message");
+        assert!(res.messages.is_empty());
+
+        // funds of a failed remote bid are credited as well
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xSender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::Bid {
+                name: "alice".to_string(),
+            }),
+            request_identifier: Some(1),
+        };
+        let _res = mock_i_receive(&mut deps, mock_info(GATEWAY, &coins(3, "token")), msg)
+            .expect("contract successfully handles IReceive message");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Credits {
+                chain_id: "80001".to_string(),
+                address: "0xSender".to_string(),
+            },
+        )
+        .unwrap();
+        let value: CreditsResponse = from_binary(&res).unwrap();
+        assert_eq!(coins(9, "token"), value.credits);
+
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xSender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::ClaimCredits {
+                recipient: "remote_recipient".to_string(),
+            }),
+            request_identifier: Some(2),
+        };
+        let res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+            .expect("contract successfully handles IReceive message");
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "remote_recipient".to_string(),
+                amount: coins(9, "token"),
+            }),
+            res.messages[0].msg
+        );
+
+        // nothing is left to claim
+        let msg = ExecuteMsg::IReceive {
+            src_chain_id: "80001".to_string(),
+            request_sender: "0xSender".to_string(),
+            packet: mock_packet(&CustomExecuteMsg::ClaimCredits {
+                recipient: "remote_recipient".to_string(),
+            }),
+            request_identifier: Some(3),
+        };
+        let res = mock_i_receive(&mut deps, mock_info(GATEWAY, &[]), msg)
+            .expect("contract successfully handles IReceive message");
+        let (success, code, _) = abi_decode_ack(&res.data.unwrap()).unwrap();
+        assert!(!success);
+        assert_eq!(ContractError::NoCredits {}.code(), code);
+    }
+
+    #[test]
+    fn returns_empty_on_query_unregistered_name() {
+        let mut deps = mock_dependencies();
+
+        mock_init_no_price(deps.as_mut());
+
+        // querying for unregistered name results in NotFound error
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ResolveRecord {
+                name: "alice".to_string(),
+                chain_id: None,
+            },
+        )
+        .unwrap();
+        let value: ResolveRecordResponse = from_binary(&res).unwrap();
+        assert_eq!(None, value.address);
+    }
+}   
+
+
+// #[test]
+// fn test_i_ack_result() {
+//     let contract_address = "osmo18f0822ahrpl3xzvuxwjfl0qz53z8cn3ncaa2fr2zf0xc2rzsytcq77k0zq".to_string();
+//     let request_identifier: u64 = 2;
+//     let exec_status = true;
+//     let exec_data = Binary::from_base64("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAACAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAJXsicmVzb2x2ZV9yZWNvcmQiOiB7Im5hbWUiOiAidGVzdDUifX0AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=").unwrap();
+//     let result_txt = format!("Ack from handler contract, \naddress:{}, \nrequest_identifier: {}, \nexec_status:{}, \nexec_data:{:?}", 
+//         contract_address, request_identifier, exec_status, exec_data);
+//     // let result_txt = "result_txt".to_string();
+//     let result = abi_encode_string(&result_txt);
+//     myprint(format!("result-txt: {}\nresult: {:?}\nbase64: {}", result_txt, result, result.to_base64()));
 // }
\ No newline at end of file